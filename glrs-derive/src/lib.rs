@@ -0,0 +1,135 @@
+//! Derive macros for glrs.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitInt, Result};
+
+/// Implements `glrs::vertex_array::VertexLayout` for a `#[repr(C)]` struct
+/// with named fields. Each field becomes one attribute, numbered from 0 in
+/// declaration order. Fields accept `#[vertex(...)]` options:
+///
+/// - `location = N` sets the attribute index; later fields continue from it.
+/// - `normalized` maps integer data to `[0, 1]` or `[-1, 1]` floats.
+/// - `skip` keeps the field in the buffer but doesn't expose it.
+#[proc_macro_derive(VertexLayout, attributes(vertex))]
+pub fn derive_vertex_layout(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+
+    if !is_repr_c(input)? {
+        return Err(Error::new_spanned(
+            name,
+            "VertexLayout needs #[repr(C)] so field offsets are stable",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    name,
+                    "VertexLayout needs a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                name,
+                "VertexLayout only works on structs",
+            ))
+        }
+    };
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut attributes = Vec::new();
+    let mut next_location = 0u32;
+
+    for field in fields {
+        let options = FieldOptions::parse(field)?;
+        if options.skip {
+            continue;
+        }
+
+        let location = options.location.unwrap_or(next_location);
+        next_location = location + 1;
+
+        let ident = field.ident.as_ref().unwrap();
+        let field_name = ident.to_string();
+        let ty = &field.ty;
+        let normalized = options.normalized;
+
+        attributes.push(quote! {
+            ::glrs::vertex_array::VertexAttribute {
+                name: #field_name,
+                location: #location,
+                components: <#ty as ::glrs::vertex_array::AttributeType>::COMPONENTS,
+                ty: <#ty as ::glrs::vertex_array::AttributeType>::GL_TYPE,
+                integer: <#ty as ::glrs::vertex_array::AttributeType>::INTEGER && !#normalized,
+                normalized: #normalized,
+                offset: ::std::mem::offset_of!(#name #ty_generics, #ident),
+            }
+        });
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::glrs::vertex_array::VertexLayout for #name #ty_generics #where_clause {
+            fn attributes() -> ::std::vec::Vec<::glrs::vertex_array::VertexAttribute> {
+                ::std::vec![#(#attributes),*]
+            }
+        }
+    })
+}
+
+fn is_repr_c(input: &DeriveInput) -> Result<bool> {
+    let mut repr_c = false;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                repr_c = true;
+            } else if meta.input.peek(syn::token::Paren) {
+                // Skip the arguments of `align(N)` and friends.
+                let _args;
+                syn::parenthesized!(_args in meta.input);
+            }
+            Ok(())
+        })?;
+    }
+    Ok(repr_c)
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    location: Option<u32>,
+    normalized: bool,
+    skip: bool,
+}
+
+impl FieldOptions {
+    fn parse(field: &syn::Field) -> Result<FieldOptions> {
+        let mut options = FieldOptions::default();
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("vertex")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("location") {
+                    let value: LitInt = meta.value()?.parse()?;
+                    options.location = Some(value.base10_parse()?);
+                } else if meta.path.is_ident("normalized") {
+                    options.normalized = true;
+                } else if meta.path.is_ident("skip") {
+                    options.skip = true;
+                } else {
+                    return Err(meta.error("expected `location = N`, `normalized` or `skip`"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(options)
+    }
+}
@@ -0,0 +1,405 @@
+use crate::clock::Clock;
+use crate::input::Input;
+use crate::playlist::Playlist;
+use crate::sampler;
+use crate::timestep::FixedTimestep;
+use crate::window::{self, WindowMode};
+use gl::types::GLsizei;
+use glutin::dpi::{LogicalSize, PhysicalPosition, PhysicalSize};
+use glutin::event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
+use glutin::event_loop::{ControlFlow, EventLoop};
+use glutin::window::{Window, WindowBuilder};
+use glutin::{ContextBuilder, GlProfile};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::error::Error;
+use std::process;
+use std::time::{Duration, Instant};
+
+/// Builds a demo. The RNG is the demo's only source of randomness, so a
+/// seeded one makes a run reproducible.
+pub type CreateFn = fn(StdRng) -> Result<Box<dyn Demo>, Box<dyn Error>>;
+
+pub trait Demo {
+    fn init(&mut self, _width: u32, _height: u32) {}
+
+    /// Advances the simulation. Called at the runner's fixed update rate, so
+    /// `dt` is the same every call while the clock runs. `input` holds the
+    /// mouse and keyboard state, with presses since the last update.
+    fn update(&mut self, _dt: f32, _input: &Input) {}
+
+    /// Draws a frame. `alpha`, from 0 to 1, is how far the frame lies
+    /// between the last update and the next, for interpolating motion.
+    fn render(&mut self, alpha: f32);
+
+    /// Called with the new framebuffer size in physical pixels whenever the
+    /// window is resized or moved to a screen with a different scale, but
+    /// never with a zero size. The runner has already set the viewport.
+    fn resize(&mut self, _width: u32, _height: u32) {}
+
+    fn handle_event(&mut self, _event: &WindowEvent) {}
+
+    fn destroy(&mut self) {}
+}
+
+pub const DEFAULT_UPDATE_RATE: f32 = 60.0;
+
+pub struct WindowConfig {
+    pub title: &'static str,
+    /// Window size in logical pixels; the framebuffer is this times the
+    /// screen's scale factor.
+    pub width: u32,
+    pub height: u32,
+    pub resizable: bool,
+    pub vsync: bool,
+    /// Seed for the demo's RNG; random when `None`.
+    pub seed: Option<u64>,
+    /// Step the simulation by this many seconds every frame instead of by
+    /// the real frame time.
+    pub fixed_dt: Option<f32>,
+    /// Simulation updates per second.
+    pub update_rate: f32,
+    /// Frames per second to stay under when vsync is off.
+    pub max_fps: Option<f32>,
+    /// The mode to start in. F11 switches between it and windowed, or
+    /// between windowed and borderless when it is windowed.
+    pub mode: WindowMode,
+    pub screensaver: Option<Screensaver>,
+}
+
+/// Runs the window as a screensaver: the cursor is hidden, and any key,
+/// click, scroll or larger mouse movement closes it. The runner's own keys
+/// are off.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Screensaver {
+    /// Physical pixels the cursor may drift from where it was first seen
+    /// without closing the window, so jitter doesn't.
+    pub mouse_threshold: f64,
+}
+
+impl Default for Screensaver {
+    fn default() -> Self {
+        Screensaver {
+            mouse_threshold: 20.0,
+        }
+    }
+}
+
+impl Screensaver {
+    // Closes on input. `origin` is where the cursor was first seen.
+    fn wakes(&self, event: &WindowEvent, origin: &mut Option<PhysicalPosition<f64>>) -> bool {
+        match *event {
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                ..
+            }
+            | WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                ..
+            }
+            | WindowEvent::MouseWheel { .. } => true,
+            WindowEvent::CursorMoved { position, .. } => {
+                let origin = *origin.get_or_insert(position);
+                let (dx, dy) = (position.x - origin.x, position.y - origin.y);
+                dx.hypot(dy) > self.mouse_threshold
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            title: " ",
+            width: 800,
+            height: 600,
+            resizable: true,
+            vsync: true,
+            seed: None,
+            fixed_dt: None,
+            update_rate: DEFAULT_UPDATE_RATE,
+            max_fps: None,
+            mode: WindowMode::Windowed,
+            screensaver: None,
+        }
+    }
+}
+
+fn create_demo(
+    create: &impl Fn(StdRng) -> Result<Box<dyn Demo>, Box<dyn Error>>,
+    seed: Option<u64>,
+) -> Result<Box<dyn Demo>, Box<dyn Error>> {
+    let rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    create(rng)
+}
+
+fn set_viewport(size: PhysicalSize<u32>) {
+    unsafe {
+        gl::Viewport(0, 0, size.width as GLsizei, size.height as GLsizei);
+    }
+}
+
+// Leaves the window as it is when the mode can't be set.
+fn switch_mode(
+    window: &Window,
+    mode: &mut WindowMode,
+    new_mode: WindowMode,
+    windowed_size: LogicalSize<u32>,
+) {
+    match window::set_mode(window, new_mode, windowed_size) {
+        Ok(()) => *mode = new_mode,
+        Err(e) => eprintln!("can't switch the window mode: {}", e),
+    }
+}
+
+// Puts back the GL state demos commonly change, so one demo's settings
+// don't leak into the next.
+pub(crate) fn reset_gl_state() {
+    unsafe {
+        gl::Disable(gl::BLEND);
+        gl::Disable(gl::DEPTH_TEST);
+        gl::Disable(gl::CULL_FACE);
+        gl::Disable(gl::PROGRAM_POINT_SIZE);
+        gl::BlendFunc(gl::ONE, gl::ZERO);
+        gl::ClearColor(0.0, 0.0, 0.0, 0.0);
+        gl::ActiveTexture(gl::TEXTURE0);
+    }
+}
+
+/// Opens a window and runs the demo until it is closed. Whatever the demo,
+/// space pauses, `.` or the right arrow steps once while paused, R starts
+/// the demo over with the same seed, and F11 toggles fullscreen.
+pub fn run(config: WindowConfig, create: CreateFn) -> ! {
+    run_with(config, create)
+}
+
+/// Runs a playlist like `run` runs a single demo. R starts the whole
+/// playlist over.
+pub fn run_playlist(config: WindowConfig, playlist: Playlist) -> ! {
+    run_with(config, move |rng| {
+        Ok(Box::new(playlist.start(rng)?) as Box<dyn Demo>)
+    })
+}
+
+/// Like `run`, for demos built from more than a seed, such as a shader file
+/// chosen on the command line. `create` is called again on every reset.
+pub fn run_with(
+    config: WindowConfig,
+    create: impl Fn(StdRng) -> Result<Box<dyn Demo>, Box<dyn Error>> + 'static,
+) -> ! {
+    let el = EventLoop::new();
+    let wb = WindowBuilder::new()
+        .with_inner_size(LogicalSize::new(config.width, config.height))
+        .with_resizable(config.resizable)
+        .with_title(config.title);
+
+    let context = ContextBuilder::new()
+        .with_gl_profile(GlProfile::Core)
+        .with_vsync(config.vsync)
+        .build_windowed(wb, &el)
+        .unwrap();
+    let context = unsafe { context.make_current().unwrap() };
+
+    gl::load_with(|symbol| context.get_proc_address(symbol));
+    sampler::forget_max_anisotropy();
+
+    window::center_window(context.window());
+    let windowed_size = LogicalSize::new(config.width, config.height);
+    let fullscreen_mode = match config.mode {
+        WindowMode::Windowed => WindowMode::Borderless { monitor: None },
+        mode => mode,
+    };
+    let mut mode = WindowMode::Windowed;
+    switch_mode(context.window(), &mut mode, config.mode, windowed_size);
+    if config.screensaver.is_some() {
+        context.window().set_cursor_visible(false);
+    }
+    let mut cursor_origin = None;
+
+    let size = context.window().inner_size();
+    set_viewport(size);
+    let mut demo = create_demo(&create, config.seed).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    demo.init(size.width, size.height);
+    let mut input = Input::new(size.width, size.height);
+    let mut step = false;
+    let mut reset = false;
+
+    let mut clock = match config.fixed_dt {
+        Some(dt) => Clock::fixed(dt),
+        None => Clock::real_time(),
+    };
+    let mut timestep = FixedTimestep::new(config.update_rate);
+
+    let frame_time = config
+        .max_fps
+        .filter(|_| !config.vsync)
+        .map(|fps| Duration::from_secs_f32(1.0 / fps));
+    let mut next_frame = Instant::now();
+
+    // Kept in an Option so the demo's GL objects are dropped while the
+    // context is still current.
+    let mut demo = Some(demo);
+
+    el.run(move |event, _, control_flow| {
+        let current = match demo.as_mut() {
+            Some(current) => current,
+            None => return,
+        };
+
+        let close = match &event {
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => true,
+            Event::WindowEvent { event, .. } => config
+                .screensaver
+                .is_some_and(|screensaver| screensaver.wakes(event, &mut cursor_origin)),
+            _ => false,
+        };
+        if close {
+            current.destroy();
+            demo = None;
+            *control_flow = ControlFlow::Exit;
+            return;
+        }
+
+        match event {
+            Event::LoopDestroyed => {}
+            Event::WindowEvent {
+                event: WindowEvent::Resized(size),
+                ..
+            }
+            | Event::WindowEvent {
+                event:
+                    WindowEvent::ScaleFactorChanged {
+                        new_inner_size: &mut size,
+                        ..
+                    },
+                ..
+            } => {
+                // Minimizing reports a zero size, which no demo can use.
+                if size.width == 0 || size.height == 0 {
+                    return;
+                }
+                context.resize(size);
+                set_viewport(size);
+                input.handle_event(&WindowEvent::Resized(size));
+                current.resize(size.width, size.height);
+            }
+            Event::WindowEvent { event, .. } => {
+                input.handle_event(&event);
+                if let WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            virtual_keycode: Some(key),
+                            state: ElementState::Pressed,
+                            ..
+                        },
+                    ..
+                } = event
+                {
+                    match key {
+                        VirtualKeyCode::Space => clock.toggle_paused(),
+                        VirtualKeyCode::Period | VirtualKeyCode::Right => step = clock.paused(),
+                        VirtualKeyCode::R => reset = true,
+                        VirtualKeyCode::F11 => {
+                            let new_mode = if mode.is_windowed() {
+                                fullscreen_mode
+                            } else {
+                                WindowMode::Windowed
+                            };
+                            switch_mode(context.window(), &mut mode, new_mode, windowed_size);
+                        }
+                        _ => {}
+                    }
+                }
+                current.handle_event(&event)
+            }
+            Event::MainEventsCleared => {
+                if let Some(frame_time) = frame_time {
+                    let now = Instant::now();
+                    if now < next_frame {
+                        *control_flow = ControlFlow::WaitUntil(next_frame);
+                        return;
+                    }
+                    // Don't try to catch up on frames missed while busy.
+                    next_frame = (next_frame + frame_time).max(now);
+                    *control_flow = ControlFlow::Poll;
+                }
+
+                if reset {
+                    reset = false;
+                    // A demo that no longer builds, say from a broken shader
+                    // file, leaves the running one up.
+                    match create_demo(&create, config.seed) {
+                        Ok(demo) => {
+                            current.destroy();
+                            reset_gl_state();
+                            *current = demo;
+                            let size = context.window().inner_size();
+                            current.init(size.width, size.height);
+                        }
+                        Err(e) => eprintln!("can't start over: {}", e),
+                    }
+                }
+
+                let mut steps = timestep.advance(clock.tick());
+                if step {
+                    step = false;
+                    steps += 1;
+                }
+                for _ in 0..steps {
+                    current.update(timestep.step(), &input);
+                    input.end_update();
+                }
+                // Nothing sees input while paused; drop it rather than
+                // replaying it all on resume.
+                if steps == 0 && clock.paused() {
+                    input.end_update();
+                }
+                context.window().request_redraw();
+            }
+            Event::RedrawRequested(_) => {
+                current.render(timestep.alpha());
+                context.swap_buffers().unwrap();
+            }
+            _ => (),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glutin::event::DeviceId;
+
+    #[allow(deprecated)]
+    fn cursor_at(x: f64, y: f64) -> WindowEvent<'static> {
+        WindowEvent::CursorMoved {
+            device_id: unsafe { DeviceId::dummy() },
+            position: PhysicalPosition::new(x, y),
+            modifiers: Default::default(),
+        }
+    }
+
+    #[test]
+    fn screensaver_ignores_jitter() {
+        let screensaver = Screensaver::default();
+        let mut origin = None;
+        assert!(!screensaver.wakes(&cursor_at(100.0, 100.0), &mut origin));
+        assert!(!screensaver.wakes(&cursor_at(110.0, 105.0), &mut origin));
+        assert!(!screensaver.wakes(&cursor_at(95.0, 100.0), &mut origin));
+        assert!(screensaver.wakes(&cursor_at(130.0, 100.0), &mut origin));
+    }
+}
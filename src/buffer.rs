@@ -0,0 +1,121 @@
+extern crate gl;
+use gl::types::*;
+use std::mem;
+use std::ptr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Usage {
+    Static,
+    Dynamic,
+    Stream,
+}
+
+impl Usage {
+    fn to_gl(self) -> GLenum {
+        match self {
+            Usage::Static => gl::STATIC_DRAW,
+            Usage::Dynamic => gl::DYNAMIC_DRAW,
+            Usage::Stream => gl::STREAM_DRAW,
+        }
+    }
+}
+
+/// An owned GL buffer object, deleted on drop.
+pub struct Buffer {
+    id: GLuint,
+    target: GLenum,
+    size: usize,
+}
+
+impl Buffer {
+    pub fn new(target: GLenum) -> Buffer {
+        let mut id = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut id);
+        }
+        Buffer {
+            id,
+            target,
+            size: 0,
+        }
+    }
+
+    pub fn array() -> Buffer {
+        Buffer::new(gl::ARRAY_BUFFER)
+    }
+
+    pub fn element_array() -> Buffer {
+        Buffer::new(gl::ELEMENT_ARRAY_BUFFER)
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    /// Size of the buffer's data store in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindBuffer(self.target, self.id);
+        }
+    }
+
+    /// Replaces the buffer's data store with `data`. `T` should be plain old
+    /// data (floats, or `#[repr(C)]` structs of them).
+    pub fn upload<T: Copy>(&mut self, data: &[T], usage: Usage) {
+        self.size = mem::size_of_val(data);
+        self.bind();
+        unsafe {
+            gl::BufferData(
+                self.target,
+                self.size as GLsizeiptr,
+                data.as_ptr() as *const GLvoid,
+                usage.to_gl(),
+            );
+        }
+    }
+
+    /// Allocates `size` bytes of uninitialized storage.
+    pub fn allocate(&mut self, size: usize, usage: Usage) {
+        self.size = size;
+        self.bind();
+        unsafe {
+            gl::BufferData(self.target, size as GLsizeiptr, ptr::null(), usage.to_gl());
+        }
+    }
+
+    /// Overwrites part of the existing data store, starting `offset` elements
+    /// of `T` into the buffer.
+    pub fn update<T: Copy>(&self, offset: usize, data: &[T]) {
+        let offset = offset * mem::size_of::<T>();
+        let size = mem::size_of_val(data);
+        assert!(
+            offset + size <= self.size,
+            "buffer update of {} bytes at {} overflows a {} byte buffer",
+            size,
+            offset,
+            self.size
+        );
+
+        self.bind();
+        unsafe {
+            gl::BufferSubData(
+                self.target,
+                offset as GLintptr,
+                size as GLsizeiptr,
+                data.as_ptr() as *const GLvoid,
+            );
+        }
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.id);
+        }
+    }
+}
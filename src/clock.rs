@@ -0,0 +1,140 @@
+use std::time::Instant;
+
+/// Where a `Clock` gets its time from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeSource {
+    /// Wall-clock time between ticks.
+    RealTime,
+    /// The same step every tick, however long the frame really took. For
+    /// offline rendering and reproducible runs.
+    Fixed(f32),
+}
+
+/// Hands out frame times. Pausing and scaling apply on top of the source,
+/// so a paused real-time clock still tracks the wall clock and doesn't jump
+/// when resumed.
+pub struct Clock {
+    source: TimeSource,
+    scale: f32,
+    paused: bool,
+    last_tick: Instant,
+    elapsed: f64,
+    frame: u64,
+}
+
+impl Clock {
+    pub fn new(source: TimeSource) -> Clock {
+        Clock {
+            source,
+            scale: 1.0,
+            paused: false,
+            last_tick: Instant::now(),
+            elapsed: 0.0,
+            frame: 0,
+        }
+    }
+
+    pub fn real_time() -> Clock {
+        Clock::new(TimeSource::RealTime)
+    }
+
+    pub fn fixed(dt: f32) -> Clock {
+        Clock::new(TimeSource::Fixed(dt))
+    }
+
+    pub fn source(&self) -> TimeSource {
+        self.source
+    }
+
+    /// Advances the clock by one frame and returns the scaled time step in
+    /// seconds, or 0 while paused.
+    pub fn tick(&mut self) -> f32 {
+        let now = Instant::now();
+        let raw = match self.source {
+            TimeSource::RealTime => now.duration_since(self.last_tick).as_secs_f32(),
+            TimeSource::Fixed(dt) => dt,
+        };
+        self.last_tick = now;
+        self.frame += 1;
+
+        let dt = if self.paused { 0.0 } else { raw * self.scale };
+        self.elapsed += dt as f64;
+        dt
+    }
+
+    /// Scaled time accumulated by `tick`, in seconds.
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+
+    /// Number of ticks so far, paused ones included.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn toggle_paused(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Multiplies every time step, e.g. 0.25 for slow motion.
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.max(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_clock_steps_the_same_every_tick() {
+        let mut clock = Clock::fixed(0.25);
+        assert_eq!(clock.source(), TimeSource::Fixed(0.25));
+        for _ in 0..4 {
+            assert_eq!(clock.tick(), 0.25);
+        }
+        assert_eq!(clock.elapsed(), 1.0);
+        assert_eq!(clock.frame(), 4);
+    }
+
+    #[test]
+    fn paused_ticks_count_frames_but_not_time() {
+        let mut clock = Clock::fixed(0.5);
+        clock.tick();
+        clock.set_paused(true);
+        assert_eq!(clock.tick(), 0.0);
+        assert_eq!(clock.tick(), 0.0);
+        clock.toggle_paused();
+        assert!(!clock.paused());
+        assert_eq!(clock.tick(), 0.5);
+        assert_eq!(clock.elapsed(), 1.0);
+        assert_eq!(clock.frame(), 4);
+    }
+
+    #[test]
+    fn scale_multiplies_each_step() {
+        let mut clock = Clock::fixed(0.5);
+        clock.set_scale(0.25);
+        assert_eq!(clock.tick(), 0.125);
+        clock.set_scale(2.0);
+        assert_eq!(clock.tick(), 1.0);
+        assert_eq!(clock.elapsed(), 1.125);
+
+        // Time doesn't run backwards.
+        clock.set_scale(-1.0);
+        assert_eq!(clock.scale(), 0.0);
+        assert_eq!(clock.tick(), 0.0);
+    }
+}
@@ -0,0 +1,88 @@
+mod particles01;
+mod particles02;
+mod particles03;
+mod texture;
+mod triangle;
+
+#[cfg(test)]
+mod tests;
+
+use glrs::app::{CreateFn, WindowConfig};
+use std::path::{Path, PathBuf};
+
+pub struct DemoInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub width: u32,
+    pub height: u32,
+    pub resizable: bool,
+    pub create: CreateFn,
+}
+
+pub static DEMOS: &[DemoInfo] = &[
+    DemoInfo {
+        name: "triangle",
+        description: "RGB triangle with per-vertex colors",
+        width: 800,
+        height: 600,
+        resizable: true,
+        create: triangle::create,
+    },
+    DemoInfo {
+        name: "texture",
+        description: "Full-screen fragment shader warping bricks.png",
+        width: 1024,
+        height: 768,
+        resizable: true,
+        create: texture::create,
+    },
+    DemoInfo {
+        name: "particles01",
+        description: "Instanced particles wrapping around the viewport",
+        width: 800,
+        height: 600,
+        resizable: true,
+        create: particles01::create,
+    },
+    DemoInfo {
+        name: "particles02",
+        description: "Point cloud tunnel animated in the vertex shader",
+        width: 1024,
+        height: 768,
+        resizable: true,
+        create: particles02::create,
+    },
+    DemoInfo {
+        name: "particles03",
+        description: "Bouncing points pushed around by wandering repulsors",
+        width: 1024,
+        height: 768,
+        resizable: true,
+        create: particles03::create,
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static DemoInfo> {
+    DEMOS.iter().find(|demo| demo.name == name)
+}
+
+impl DemoInfo {
+    pub fn window_config(&self) -> WindowConfig {
+        WindowConfig {
+            width: self.width,
+            height: self.height,
+            resizable: self.resizable,
+            ..WindowConfig::default()
+        }
+    }
+}
+
+/// Where the demos read assets such as `bricks.png`: the working directory,
+/// or the crate root under test so results don't depend on where tests run.
+fn asset(name: &str) -> PathBuf {
+    if cfg!(test) {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join(name)
+    } else {
+        PathBuf::from(name)
+    }
+}
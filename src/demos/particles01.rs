@@ -0,0 +1,207 @@
+extern crate gl;
+use glrs::app::Demo;
+use glrs::input::{Input, MouseButton};
+use glrs::particles::{
+    Attractor, Bounds, Curve, CurveEvaluation, Edge, Emitter, Falloff, Force, LifeCurves,
+    ParticleRenderer, ParticleSystem, Particles, Shape, ShapeEmitter, Sprite, WorldResize,
+    LIFE_CURVES_GLSL, LIFE_CURVES_INCLUDE,
+};
+use glrs::reload::{ReloadableProgram, ShaderFile};
+use glrs::shader::ShaderError;
+use rand::rngs::StdRng;
+use std::error::Error;
+
+static VS_SRC: &str = "
+#version 410
+
+layout(location=0) in vec2 a_corner;
+layout(location=1) in vec3 a_position;
+layout(location=2) in float a_size;
+layout(location=3) in vec4 a_color;
+layout(location=4) in float a_life;
+
+uniform vec2 u_resolution;
+
+out vec4 v_color;
+
+#include \"glrs/clip_space.glsl\"
+#include \"glrs/particles.glsl\"
+
+void main() {
+	vec2 vp = a_position.xy + a_corner * a_size * life_size(a_life);
+
+	gl_Position = vec4(pixel_to_clip(vp, u_resolution), 0.0, 1.0);
+	v_color = a_color * life_color(a_life);
+}
+";
+
+static FS_SRC: &str = "
+#version 410
+
+in vec4 v_color;
+out vec4 out_color;
+
+void main() {
+  out_color = v_color;
+}
+";
+
+const MAX_PARTICLES: usize = 5000;
+// Room for the particles clicks spawn on top of the steady ones.
+const MAX_BURST_PARTICLES: usize = 2000;
+const BURST: usize = 300;
+// In pixels per second.
+const MAX_SPEED: f32 = 120.0;
+const MAX_LIFETIME: f32 = 30.0;
+// A bigger window shows more particles rather than bigger ones.
+const ON_RESIZE: WorldResize = WorldResize::Extend;
+// Cools from white to orange as particles age.
+const GLOW: &str = "0:#ffffff 0.6:#ffe0b0 1:#ff7030";
+// Acceleration at the cursor, in pixels per second squared.
+const CURSOR_STRENGTH: f32 = 2000.0;
+const CURSOR_RADIUS: std::ops::RangeInclusive<f32> = 20.0..=600.0;
+// Radius change per line scrolled.
+const CURSOR_ZOOM: f32 = 1.2;
+
+/// Replaces particles as they die, up to `MAX_PARTICLES`, so the rest of the
+/// pool stays free for bursts.
+struct Respawn(ShapeEmitter);
+
+impl Emitter for Respawn {
+    fn emit(&mut self, particles: &mut Particles, _dt: f32, rng: &mut StdRng) {
+        while particles.len() < MAX_PARTICLES {
+            match self.0.particle(rng) {
+                Some(particle) => particles.spawn(particle),
+                None => break,
+            };
+        }
+    }
+
+    fn resize(&mut self, scale: [f32; 2]) {
+        self.0.resize(scale);
+    }
+}
+
+pub struct Particles01 {
+    program: ReloadableProgram,
+    renderer: ParticleRenderer,
+    system: ParticleSystem,
+    rng: StdRng,
+    viewport_width: f32,
+    viewport_height: f32,
+    cursor_radius: f32,
+}
+
+pub fn create(rng: StdRng) -> Result<Box<dyn Demo>, Box<dyn Error>> {
+    Ok(Box::new(Particles01::new(rng)?))
+}
+
+impl Particles01 {
+    pub fn new(rng: StdRng) -> Result<Self, ShaderError> {
+        let program = ReloadableProgram::new(
+            ShaderFile::new("shaders/particles01.vert", VS_SRC)
+                .builtin(LIFE_CURVES_INCLUDE, LIFE_CURVES_GLSL),
+            ShaderFile::new("shaders/particles01.frag", FS_SRC),
+        )?;
+
+        Ok(Particles01 {
+            program,
+            renderer: ParticleRenderer::new(MAX_PARTICLES + MAX_BURST_PARTICLES, Sprite::Quad)
+                .evaluation(CurveEvaluation::Gpu),
+            system: ParticleSystem::new(MAX_PARTICLES),
+            rng,
+            viewport_width: 0.0,
+            viewport_height: 0.0,
+            cursor_radius: 120.0,
+        })
+    }
+}
+
+impl Demo for Particles01 {
+    fn init(&mut self, width: u32, height: u32) {
+        let (width, height) = (width as f32, height as f32);
+        self.viewport_width = width;
+        self.viewport_height = height;
+
+        // Start full, then replace particles as they die.
+        let fill = ShapeEmitter::new(Shape::Rect {
+            min: [0.0, 0.0],
+            max: [width, height],
+        })
+        .burst(MAX_PARTICLES)
+        .speed(0.0..=MAX_SPEED)
+        .lifetime(0.0..=MAX_LIFETIME);
+        let respawn = Respawn(fill.clone());
+
+        self.system = ParticleSystem::new(MAX_PARTICLES + MAX_BURST_PARTICLES)
+            .emitter(fill)
+            .emitter(respawn)
+            .bounds(Bounds::rect([0.0, 0.0], [width, height], Edge::Wrap))
+            .curves(
+                LifeCurves::new()
+                    .size(Curve::new(vec![(0.0, 0.5), (0.2, 1.0), (1.0, 1.5)]))
+                    .color(GLOW.parse().expect("GLOW is a valid gradient"))
+                    .fade(0.05, 0.2),
+            );
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        let (width, height) = (width as f32, height as f32);
+        let scale = [width / self.viewport_width, height / self.viewport_height];
+        self.system.resize_world(scale, ON_RESIZE);
+        self.viewport_width = width;
+        self.viewport_height = height;
+    }
+
+    /// The cursor repels particles, or attracts them while the right button
+    /// is held, within a radius the wheel changes. Left clicks spawn bursts.
+    fn update(&mut self, dt: f32, input: &Input) {
+        self.program.poll();
+
+        self.cursor_radius = (self.cursor_radius * CURSOR_ZOOM.powf(input.scroll()))
+            .clamp(*CURSOR_RADIUS.start(), *CURSOR_RADIUS.end());
+
+        // The world is in pixels from the top left, like the cursor.
+        if let Some([x, y]) = input.cursor() {
+            let particles = self.system.particles_mut();
+            let strength = if input.button_down(MouseButton::Right) {
+                CURSOR_STRENGTH
+            } else {
+                -CURSOR_STRENGTH
+            };
+            Attractor::new([x, y, 0.0], strength)
+                .radius(self.cursor_radius)
+                .falloff(Falloff::Linear)
+                .apply(particles, dt, &mut self.rng);
+
+            if input.button_pressed(MouseButton::Left) {
+                ShapeEmitter::new(Shape::Circle {
+                    center: [x, y],
+                    radius: self.cursor_radius / 4.0,
+                })
+                .burst(BURST)
+                .speed(MAX_SPEED..=MAX_SPEED * 3.0)
+                .size(1.0..=2.0)
+                .lifetime(0.5..=2.0)
+                .emit(particles, dt, &mut self.rng);
+            }
+        }
+        self.system.update(dt, &mut self.rng);
+    }
+
+    fn render(&mut self, alpha: f32) {
+        self.program.bind();
+        self.program
+            .set("u_resolution", [self.viewport_width, self.viewport_height]);
+        self.renderer.stream(&self.system, alpha);
+
+        unsafe {
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+
+            gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+        self.renderer.draw();
+    }
+}
@@ -0,0 +1,128 @@
+extern crate gl;
+use glrs::app::Demo;
+use glrs::input::Input;
+use glrs::particles::{Bounds, Edge, Particle, ParticleRenderer, ParticleSystem, Sprite};
+use glrs::reload::{ReloadableProgram, ShaderFile};
+use glrs::shader::ShaderError;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::error::Error;
+
+static VS_SRC: &str = "
+#version 410
+
+layout(location=1) in vec3 a_position;
+uniform float T;
+uniform float u_aspect;
+out float Z;
+
+// The tunnel is framed for 4:3; other shapes see more or less of it sideways.
+const float FRAMED_ASPECT = 4.0 / 3.0;
+
+void main() {
+    float N = 0.0;
+    float O = 1.0;
+    vec4 v = vec4(a_position, O);
+    v.z += 2.0 * (sin(T * 2.0 + v.x) + cos(T * 2.0 + v.y * 1.5));
+    gl_PointSize = 8.0 + sin(T * 3.5) + cos(T / 2.0);
+    gl_Position = mat4(7.0 * FRAMED_ASPECT / u_aspect, N, N, N,
+                       N, 7, N, N,
+                       N, N, -O, -O,
+                       5.0 * sin(T), 5 * sin(T / 3.15), N, O) * v;
+    Z = O - gl_Position.z / 8.0;
+}
+";
+
+static FS_SRC: &str = "
+#version 410
+
+in float Z;
+out vec4 out_color;
+
+void main() {
+  out_color = vec4(Z, Z, Z, 1.0);
+}
+";
+
+const MAX_PARTICLES: usize = 5000;
+// The tunnel is 30 units deep and scrolls towards the camera.
+const DEPTH: f32 = 30.0;
+const SPEED: f32 = 2.0;
+
+pub struct Particles02 {
+    program: ReloadableProgram,
+    renderer: ParticleRenderer,
+    system: ParticleSystem,
+    rng: StdRng,
+    aspect: f32,
+    time: f32,
+}
+
+pub fn create(rng: StdRng) -> Result<Box<dyn Demo>, Box<dyn Error>> {
+    Ok(Box::new(Particles02::new(rng)?))
+}
+
+impl Particles02 {
+    pub fn new(mut rng: StdRng) -> Result<Self, ShaderError> {
+        let program = ReloadableProgram::new(
+            ShaderFile::new("shaders/particles02.vert", VS_SRC),
+            ShaderFile::new("shaders/particles02.frag", FS_SRC),
+        )?;
+
+        let half = DEPTH / 2.0;
+        let mut system = ParticleSystem::new(MAX_PARTICLES).bounds(Bounds::new(
+            [f32::NEG_INFINITY, f32::NEG_INFINITY, -half],
+            [f32::INFINITY, f32::INFINITY, half],
+            Edge::Wrap,
+        ));
+        for k in 0..MAX_PARTICLES {
+            let x = rng.gen::<f32>() * 2.0 - 1.0;
+            let y = rng.gen::<f32>() * 2.0 - 1.0;
+            system.particles_mut().spawn(Particle {
+                position: [x, y, (k as f32 % DEPTH) - half],
+                velocity: [0.0, 0.0, SPEED],
+                ..Particle::default()
+            });
+        }
+
+        Ok(Particles02 {
+            program,
+            renderer: ParticleRenderer::new(MAX_PARTICLES, Sprite::Point),
+            system,
+            rng,
+            aspect: 1.0,
+            time: 0.0,
+        })
+    }
+}
+
+impl Demo for Particles02 {
+    fn init(&mut self, width: u32, height: u32) {
+        self.resize(width, height);
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.aspect = width as f32 / height as f32;
+    }
+
+    fn update(&mut self, dt: f32, _input: &Input) {
+        self.time += dt;
+        self.program.poll();
+
+        self.system.update(dt, &mut self.rng);
+    }
+
+    fn render(&mut self, alpha: f32) {
+        self.program.bind();
+        self.program.set("T", self.time);
+        self.program.set("u_aspect", self.aspect);
+        self.renderer.stream(&self.system, alpha);
+
+        unsafe {
+            gl::Enable(gl::PROGRAM_POINT_SIZE);
+            gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
+        self.renderer.draw();
+    }
+}
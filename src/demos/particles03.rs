@@ -0,0 +1,249 @@
+extern crate gl;
+use glrs::app::Demo;
+use glrs::input::{Input, MouseButton};
+use glrs::particles::{
+    Attractor, Bounds, Curve, Edge, Emitter, Falloff, Force, LifeCurves, ParticleRenderer,
+    ParticleSystem, Particles, Shape, ShapeEmitter, Sprite,
+};
+use glrs::reload::{ReloadableProgram, ShaderFile};
+use glrs::shader::ShaderError;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::error::Error;
+
+static VS_SRC: &str = "
+#version 330
+
+layout(location=1) in vec3 a_position;
+layout(location=2) in float a_size;
+layout(location=3) in vec4 a_color;
+
+out vec4 v_color;
+
+void main() {
+    gl_Position = vec4(vec3(a_position.xy, 1.0), 1.0);
+    gl_PointSize = a_size;
+    v_color = a_color;
+}
+";
+
+static FS_SRC: &str = "
+#version 330
+
+in vec4 v_color;
+out vec4 out_color;
+
+void main() {
+  out_color = v_color;
+}
+";
+
+/// A circle that drifts towards a random target, then picks a new one.
+struct Wanderer {
+    x: f32,
+    y: f32,
+    r: f32,
+    nx: f32,
+    ny: f32,
+    nr: f32,
+    ex: f32,
+    ey: f32,
+    er: f32,
+}
+
+/// Wandering circles that push particles out of the way and make them
+/// flare up.
+struct Wanderers(Vec<Wanderer>);
+
+const MAX_PARTICLES: usize = 7000;
+// Room for the particles clicks spawn on top of the starting ones.
+const MAX_BURST_PARTICLES: usize = 3000;
+const BURST: usize = 300;
+const MAX_WANDERERS: usize = 8;
+const RESTITUTION: f32 = 0.96;
+// Point size lost per second for each wanderer, down to 2 pixels.
+const SHRINK: f32 = 6.0;
+// Updates per second the wanderers' easing factors were tuned at.
+const WANDER_RATE: f32 = 60.0;
+// In clip space units per second.
+const MAX_SPEED: f32 = 1.8;
+// Acceleration at the cursor, in clip space units per second squared.
+const CURSOR_STRENGTH: f32 = 30.0;
+const CURSOR_RADIUS: std::ops::RangeInclusive<f32> = 0.05..=1.0;
+// Radius change per line scrolled.
+const CURSOR_ZOOM: f32 = 1.2;
+
+pub struct Particles03 {
+    program: ReloadableProgram,
+    renderer: ParticleRenderer,
+    system: ParticleSystem,
+    rng: StdRng,
+    cursor_radius: f32,
+}
+
+pub fn create(rng: StdRng) -> Result<Box<dyn Demo>, Box<dyn Error>> {
+    Ok(Box::new(Particles03::new(rng)?))
+}
+
+impl Particles03 {
+    pub fn new(mut rng: StdRng) -> Result<Self, ShaderError> {
+        let program = ReloadableProgram::new(
+            ShaderFile::new("shaders/particles03.vert", VS_SRC),
+            ShaderFile::new("shaders/particles03.frag", FS_SRC),
+        )?;
+
+        let mut wanderers: Vec<Wanderer> = Vec::new();
+        for _ in 0..MAX_WANDERERS {
+            wanderers.push(Wanderer {
+                x: -1.0 + rng.gen::<f32>() * (1.0 - -1.0),
+                y: -1.0 + rng.gen::<f32>() * (1.0 - -1.0),
+                r: 0.1,
+                nx: -1.0 + rng.gen::<f32>() * (1.0 - -1.0),
+                ny: -1.0 + rng.gen::<f32>() * (1.0 - -1.0),
+                nr: 0.1 + rng.gen::<f32>() * (1.0 - 0.1),
+                ex: 0.02 + rng.gen::<f32>() * (0.01 - 0.02),
+                ey: 0.02 + rng.gen::<f32>() * (0.01 - 0.02),
+                er: 0.02 + rng.gen::<f32>() * (0.01 - 0.02),
+            });
+        }
+
+        let points = ShapeEmitter::new(Shape::Rect {
+            min: [-1.0, -1.0],
+            max: [1.0, 1.0],
+        })
+        .burst(MAX_PARTICLES)
+        .speed(0.0..=MAX_SPEED)
+        .size(1.0..=5.0);
+
+        let system = ParticleSystem::new(MAX_PARTICLES + MAX_BURST_PARTICLES)
+            .emitter(points)
+            .force(Wanderers(wanderers))
+            .bounds(Bounds::rect(
+                [-1.0, -1.0],
+                [1.0, 1.0],
+                Edge::Bounce(RESTITUTION),
+            ))
+            // Only burst particles die; the rest stay at full size.
+            .curves(LifeCurves::new().size(Curve::linear(1.0, 0.0)));
+
+        Ok(Particles03 {
+            program,
+            renderer: ParticleRenderer::new(MAX_PARTICLES + MAX_BURST_PARTICLES, Sprite::Point),
+            system,
+            rng,
+            cursor_radius: 0.2,
+        })
+    }
+}
+
+impl Force for Wanderers {
+    fn apply(&mut self, particles: &mut Particles, dt: f32, rng: &mut StdRng) {
+        let attributes = particles.attributes_mut();
+
+        for (position, size) in attributes.position.iter_mut().zip(attributes.size) {
+            for wanderer in &self.0 {
+                let dx = position[0] - wanderer.x;
+                let dy = position[1] - wanderer.y;
+                let dist = (dx * dx + dy * dy).sqrt();
+
+                if dist < wanderer.r {
+                    position[0] = wanderer.x + dx / dist * wanderer.r;
+                    position[1] = wanderer.y + dy / dist * wanderer.r;
+                    *size = 1.0 + rng.gen::<f32>() * (5.0 - 1.0);
+                }
+
+                if *size > 2.0 {
+                    *size -= SHRINK * dt;
+                }
+            }
+        }
+
+        for wanderer in self.0.iter_mut() {
+            if !wander(wanderer, dt) {
+                wanderer.ex = 0.07 + rng.gen::<f32>() * (0.01 - 0.07);
+                wanderer.ey = 0.05 + rng.gen::<f32>() * (0.01 - 0.05);
+                wanderer.er = 0.01 + rng.gen::<f32>() * (0.05 - 0.01);
+
+                wanderer.nx = -0.9 + rng.gen::<f32>() * (1.0 - -0.9);
+                wanderer.ny = -0.9 + rng.gen::<f32>() * (1.0 - -0.9);
+                wanderer.nr = 0.01 + rng.gen::<f32>() * (0.3 - 0.01);
+            }
+        }
+    }
+}
+
+impl Demo for Particles03 {
+    /// The cursor repels particles, or attracts them while the right button
+    /// is held, within a radius the wheel changes. Left clicks spawn bursts.
+    fn update(&mut self, dt: f32, input: &Input) {
+        self.program.poll();
+
+        self.cursor_radius = (self.cursor_radius * CURSOR_ZOOM.powf(input.scroll()))
+            .clamp(*CURSOR_RADIUS.start(), *CURSOR_RADIUS.end());
+
+        if let Some([x, y]) = input.cursor_clip() {
+            let particles = self.system.particles_mut();
+            let strength = if input.button_down(MouseButton::Right) {
+                CURSOR_STRENGTH
+            } else {
+                -CURSOR_STRENGTH
+            };
+            Attractor::new([x, y, 0.0], strength)
+                .radius(self.cursor_radius)
+                .falloff(Falloff::Linear)
+                .apply(particles, dt, &mut self.rng);
+
+            if input.button_pressed(MouseButton::Left) {
+                ShapeEmitter::new(Shape::Circle {
+                    center: [x, y],
+                    radius: self.cursor_radius / 4.0,
+                })
+                .burst(BURST)
+                .speed(0.2..=MAX_SPEED)
+                .size(2.0..=6.0)
+                .lifetime(0.5..=2.0)
+                .emit(particles, dt, &mut self.rng);
+            }
+        }
+
+        self.system.update(dt, &mut self.rng);
+    }
+
+    fn render(&mut self, alpha: f32) {
+        self.program.bind();
+        self.renderer.stream(&self.system, alpha);
+
+        unsafe {
+            gl::Enable(gl::PROGRAM_POINT_SIZE);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
+        self.renderer.draw();
+    }
+}
+
+fn wander(wanderer: &mut Wanderer, dt: f32) -> bool {
+    // The fraction of the way left covered in `dt`, for a fraction per step
+    // at WANDER_RATE.
+    let ease = |e: f32| 1.0 - (1.0 - e).powf(dt * WANDER_RATE);
+
+    let mut vx = wanderer.nx - wanderer.x;
+    let mut vy = wanderer.ny - wanderer.y;
+    let mut vr = wanderer.nr - wanderer.r;
+
+    let ax = vx.abs() > 0.1;
+    let ay = vy.abs() > 0.1;
+    let ar = vr.abs() > 0.1;
+
+    if ax && ay && ar {
+        vx *= ease(wanderer.ex);
+        vy *= ease(wanderer.ey);
+        vr *= ease(wanderer.er);
+
+        wanderer.x += vx;
+        wanderer.y += vy;
+        wanderer.r += vr;
+        true
+    } else {
+        false
+    }
+}
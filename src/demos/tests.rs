@@ -0,0 +1,134 @@
+//! Golden-image tests: each demo is rendered offscreen with a fixed time
+//! step and RNG seed, and one frame is compared against the reference in
+//! `tests/golden/`. Run with `GLRS_BLESS=1` to regenerate the references
+//! after an intended change. Without a headless GL context the tests fail,
+//! unless `GLRS_SKIP_GOLDEN` is set. Demos compile their built-in shaders,
+//! so edits under `shaders/` don't change the results.
+
+use glrs::app::Demo;
+use glrs::golden::{self, Tolerance};
+use glrs::headless::{self, HeadlessError, RenderConfig};
+use glrs::playlist::{Playlist, Transition};
+use glrs::shadertoy::Shadertoy;
+use rand::rngs::StdRng;
+use std::env;
+use std::error::Error;
+use std::path::Path;
+use std::sync::Mutex;
+
+const WIDTH: u32 = 256;
+const HEIGHT: u32 = 192;
+const FRAME: u32 = 30;
+const SEED: u64 = 1;
+
+// The GL function pointers are process-wide, so render one demo at a time.
+static GL: Mutex<()> = Mutex::new(());
+
+fn check_demo(name: &str) {
+    check(name, super::find(name).unwrap().create);
+}
+
+fn check(name: &str, create: impl FnOnce(StdRng) -> Result<Box<dyn Demo>, Box<dyn Error>>) {
+    let _gl = GL.lock().unwrap_or_else(|e| e.into_inner());
+    let config = RenderConfig {
+        width: WIDTH,
+        height: HEIGHT,
+        frames: FRAME + 1,
+        dt: 1.0 / 60.0,
+        update_rate: 60.0,
+        seed: SEED,
+        builtin_shaders: true,
+    };
+
+    let mut captured = None;
+    let result = headless::run(&config, create, |frame, pixels| {
+        if frame == FRAME {
+            captured = Some(pixels);
+        }
+        Ok(())
+    });
+    match result {
+        Ok(()) => {}
+        Err(HeadlessError::NoContext(reasons)) if env::var_os("GLRS_SKIP_GOLDEN").is_some() => {
+            eprintln!("skipping {}: {}", name, reasons.join("; "));
+            return;
+        }
+        Err(e @ HeadlessError::NoContext(_)) => {
+            panic!("{}set GLRS_SKIP_GOLDEN=1 to skip the golden tests", e)
+        }
+        Err(e) => panic!("{}", e),
+    }
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    if let Err(e) = golden::check(
+        name,
+        &root.join("tests/golden"),
+        &root.join("target/golden"),
+        WIDTH,
+        HEIGHT,
+        &captured.unwrap(),
+        &Tolerance::default(),
+    ) {
+        panic!("{}", e);
+    }
+}
+
+#[test]
+fn triangle() {
+    check_demo("triangle");
+}
+
+#[test]
+fn texture() {
+    check_demo("texture");
+}
+
+#[test]
+fn particles01() {
+    check_demo("particles01");
+}
+
+#[test]
+fn particles02() {
+    check_demo("particles02");
+}
+
+#[test]
+fn particles03() {
+    check_demo("particles03");
+}
+
+#[test]
+fn every_demo_has_a_reference() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    for demo in super::DEMOS {
+        let reference = root.join(format!("tests/golden/{}.png", demo.name));
+        assert!(
+            reference.exists(),
+            "{} has no golden image, add a test for it and run with GLRS_BLESS=1",
+            demo.name
+        );
+    }
+}
+
+// Halfway through a crossfade at the captured frame.
+#[test]
+fn playlist_crossfade() {
+    let playlist = Playlist::new()
+        .add(super::find("particles02").unwrap().create, 0.25)
+        .add(super::find("texture").unwrap().create, 1.0)
+        .transition(Transition::Crossfade, 0.5);
+    check("playlist_crossfade", |rng| {
+        Ok(Box::new(playlist.start(rng)?) as Box<dyn Demo>)
+    });
+}
+
+#[test]
+fn shadertoy_ripples() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let shader = root.join("tests/shadertoy/ripples.glsl");
+    let channels = [Some(root.join("bricks.png")), None, None, None];
+    check("shadertoy_ripples", |_| {
+        Ok(Box::new(Shadertoy::new(&shader, &channels)?) as Box<dyn Demo>)
+    });
+}
@@ -0,0 +1,116 @@
+extern crate gl;
+use glrs::app::Demo;
+use glrs::buffer::{Buffer, Usage};
+use glrs::input::Input;
+use glrs::reload::{ReloadableProgram, ShaderFile};
+use glrs::sampler::SamplerOptions;
+use glrs::texture::{self, Texture2D, TextureOptions};
+use glrs::vertex_array::VertexArray;
+use rand::rngs::StdRng;
+use std::error::Error;
+
+static VS_SRC: &str = "
+#version 330
+layout(location=0) in vec2 a_position;
+out vec2 v_texcoord;
+
+void main() {
+    gl_Position = vec4(a_position, 0.0, 1.0);
+    v_texcoord = -0.5 + a_position + 1.0;
+}
+";
+
+static FS_SRC: &str = "
+#version 330
+uniform sampler2D u_sampler;
+uniform vec2 u_resolution;
+uniform float u_time;
+
+in vec2 v_texcoord;
+out vec4 out_color;
+
+void main() {
+    vec2 uv = gl_FragCoord.xy / u_resolution;
+    vec2 direction = vec2(uv.xy - vec2(0.5, 0.5));
+    uv *= length(direction);
+    uv.x *= cos(u_time * 0.08);
+    uv.y += sin(u_time * 0.03);
+
+    out_color = texture(u_sampler, uv);
+}
+";
+
+pub struct Texture {
+    program: ReloadableProgram,
+    vao: VertexArray,
+    // Only held so the vertex array's buffer outlives it.
+    _vertex_vbo: Buffer,
+    texture: Texture2D,
+    resolution: [f32; 2],
+    time: f32,
+}
+
+pub fn create(_rng: StdRng) -> Result<Box<dyn Demo>, Box<dyn Error>> {
+    Ok(Box::new(Texture::new()?))
+}
+
+impl Texture {
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let program = ReloadableProgram::new(
+            ShaderFile::new("shaders/texture.vert", VS_SRC),
+            ShaderFile::new("shaders/texture.frag", FS_SRC),
+        )?;
+
+        let vertices: Vec<f32> = vec![
+            0.5, -0.5, -0.5, 0.5, -0.5, -0.5, 0.5, 0.5, -0.5, 0.5, 0.5, -0.5,
+        ];
+
+        let vao = VertexArray::new();
+        let mut vertex_vbo = Buffer::array();
+        vertex_vbo.upload(&vertices, Usage::Static);
+        vao.attrib_f32(&vertex_vbo, 0, 2, 0, 0, 0);
+
+        let texture = texture::load_texture(
+            super::asset("bricks.png"),
+            &TextureOptions::new().sampler(SamplerOptions::new().anisotropy(8.0)),
+        )?;
+
+        Ok(Texture {
+            program,
+            vao,
+            _vertex_vbo: vertex_vbo,
+            texture,
+            resolution: [0.0, 0.0],
+            time: 0.0,
+        })
+    }
+}
+
+impl Demo for Texture {
+    fn init(&mut self, width: u32, height: u32) {
+        self.resolution = [width as f32, height as f32];
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.resolution = [width as f32, height as f32];
+    }
+
+    fn update(&mut self, dt: f32, _input: &Input) {
+        self.time += dt;
+        self.program.poll();
+    }
+
+    fn render(&mut self, _alpha: f32) {
+        self.texture.bind(0);
+        self.vao.bind();
+        self.program.set("u_sampler", 0);
+        self.program.set("u_resolution", self.resolution);
+        self.program.set("u_time", self.time);
+
+        unsafe {
+            gl::ClearColor(1.0, 1.0, 1.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            gl::DrawArrays(gl::TRIANGLES, 0, 6);
+        }
+    }
+}
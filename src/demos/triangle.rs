@@ -0,0 +1,102 @@
+extern crate gl;
+use glrs::app::Demo;
+use glrs::buffer::{Buffer, Usage};
+use glrs::input::Input;
+use glrs::reload::{ReloadableProgram, ShaderFile};
+use glrs::shader::ShaderError;
+use glrs::vertex_array::{VertexArray, VertexLayout};
+use rand::rngs::StdRng;
+use std::error::Error;
+
+static VS_SRC: &str = "
+#version 150
+in vec3 position;
+in vec3 color;
+out vec3 v_color;
+
+void main() {
+    gl_Position = vec4(position,  1.0);
+    v_color = color;
+}";
+
+static FS_SRC: &str = "
+#version 150
+in vec3 v_color;
+out vec4 out_color;
+
+void main() {
+    out_color = vec4(v_color, 1.0);
+}";
+
+#[repr(C)]
+#[derive(Clone, Copy, VertexLayout)]
+struct Vertex {
+    position: [f32; 3],
+    color: [f32; 3],
+}
+
+pub struct Triangle {
+    program: ReloadableProgram,
+    vao: VertexArray,
+    vbo: Buffer,
+}
+
+pub fn create(_rng: StdRng) -> Result<Box<dyn Demo>, Box<dyn Error>> {
+    Ok(Box::new(Triangle::new()?))
+}
+
+impl Triangle {
+    pub fn new() -> Result<Self, ShaderError> {
+        let vertices = [
+            Vertex {
+                position: [0.5, -0.5, 0.0],
+                color: [1.0, 0.0, 0.0],
+            },
+            Vertex {
+                position: [-0.5, -0.5, 0.0],
+                color: [0.0, 1.0, 0.0],
+            },
+            Vertex {
+                position: [0.0, 0.5, 0.0],
+                color: [0.0, 0.0, 1.0],
+            },
+        ];
+
+        let program = ReloadableProgram::new(
+            ShaderFile::new("shaders/triangle.vert", VS_SRC),
+            ShaderFile::new("shaders/triangle.frag", FS_SRC),
+        )?;
+
+        let vao = VertexArray::new();
+        let mut vbo = Buffer::array();
+        vbo.upload(&vertices, Usage::Static);
+
+        let triangle = Triangle { program, vao, vbo };
+        triangle.bind_attributes();
+        Ok(triangle)
+    }
+
+    fn bind_attributes(&self) {
+        self.vao
+            .bind_layout_to::<Vertex>(&self.vbo, &self.program, 0);
+    }
+}
+
+impl Demo for Triangle {
+    fn update(&mut self, _dt: f32, _input: &Input) {
+        if self.program.poll() {
+            self.bind_attributes();
+        }
+    }
+
+    fn render(&mut self, _alpha: f32) {
+        self.program.bind();
+        self.vao.bind();
+
+        unsafe {
+            gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        }
+    }
+}
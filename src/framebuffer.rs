@@ -0,0 +1,163 @@
+extern crate gl;
+use super::sampler::{Filter, SamplerOptions, Wrap};
+use super::texture::{Texture2D, TextureFormat, TextureOptions};
+use gl::types::*;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FramebufferError {
+    pub status: GLenum,
+}
+
+impl fmt::Display for FramebufferError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self.status {
+            gl::FRAMEBUFFER_UNDEFINED => "undefined",
+            gl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => "incomplete attachment",
+            gl::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => "missing attachment",
+            gl::FRAMEBUFFER_UNSUPPORTED => "unsupported format combination",
+            gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => "inconsistent multisampling",
+            _ => "unknown status",
+        };
+        write!(
+            f,
+            "framebuffer is incomplete: {} (0x{:04x})",
+            reason, self.status
+        )
+    }
+}
+
+impl Error for FramebufferError {}
+
+/// An owned framebuffer object with an RGBA8 color texture and a
+/// depth/stencil renderbuffer, all deleted on drop.
+pub struct Framebuffer {
+    id: GLuint,
+    color: Texture2D,
+    depth_stencil: GLuint,
+}
+
+impl Framebuffer {
+    pub fn new(width: i32, height: i32) -> Result<Framebuffer, FramebufferError> {
+        let mut color = Texture2D::new();
+        let options = TextureOptions::new().mipmaps(false).sampler(
+            SamplerOptions::new()
+                .wrap(Wrap::ClampToEdge)
+                .filter(Filter::Linear),
+        );
+        color.allocate(width, height, TextureFormat::Rgba8, &options);
+
+        let mut id = 0;
+        let mut depth_stencil = 0;
+        unsafe {
+            gl::GenRenderbuffers(1, &mut depth_stencil);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, depth_stencil);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH24_STENCIL8, width, height);
+
+            gl::GenFramebuffers(1, &mut id);
+        }
+
+        // Constructed before the status check so everything is freed on error.
+        let framebuffer = Framebuffer {
+            id,
+            color,
+            depth_stencil,
+        };
+        framebuffer.bind();
+
+        unsafe {
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                framebuffer.color.id(),
+                0,
+            );
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_STENCIL_ATTACHMENT,
+                gl::RENDERBUFFER,
+                depth_stencil,
+            );
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return Err(FramebufferError { status });
+            }
+        }
+
+        Ok(framebuffer)
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn width(&self) -> i32 {
+        self.color.width()
+    }
+
+    pub fn height(&self) -> i32 {
+        self.color.height()
+    }
+
+    /// The texture holding the rendered image.
+    pub fn color(&self) -> &Texture2D {
+        &self.color
+    }
+
+    /// Makes this the render target and sets the viewport to cover it.
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+            gl::Viewport(0, 0, self.width(), self.height());
+        }
+    }
+
+    /// Goes back to rendering into the window.
+    pub fn unbind() {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    /// Reads the color attachment back as tightly packed RGBA8 rows, top row
+    /// first.
+    pub fn read_pixels(&self) -> Vec<u8> {
+        let (width, height) = (self.width() as usize, self.height() as usize);
+        let row = width * 4;
+        let mut pixels = vec![0u8; row * height];
+
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.id);
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::ReadPixels(
+                0,
+                0,
+                width as GLsizei,
+                height as GLsizei,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_mut_ptr() as *mut GLvoid,
+            );
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+        }
+
+        // GL's origin is the bottom left, image files start at the top.
+        let mut flipped = Vec::with_capacity(pixels.len());
+        for line in pixels.chunks_exact(row).rev() {
+            flipped.extend_from_slice(line);
+        }
+        flipped
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.id);
+            gl::DeleteRenderbuffers(1, &self.depth_stencil);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use crate::headless::write_png;
+use std::env;
+use std::error::Error;
+use std::fs::{self, File};
+use std::path::Path;
+
+/// Set to regenerate the reference images instead of checking against them.
+pub const BLESS_VAR: &str = "GLRS_BLESS";
+
+/// How far a rendered frame may drift from its reference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// Perceptual color difference, from 0 to 1, above which a pixel counts
+    /// as changed.
+    pub threshold: f32,
+    /// Fraction of pixels that may change before the comparison fails.
+    pub max_changed: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Tolerance {
+            threshold: 0.1,
+            max_changed: 0.002,
+        }
+    }
+}
+
+pub struct Comparison {
+    pub changed: usize,
+    pub total: usize,
+    /// Largest perceptual difference of any pixel, from 0 to 1.
+    pub max_delta: f32,
+    /// The reference faded to gray with changed pixels in red, as RGBA8.
+    pub diff: Vec<u8>,
+}
+
+impl Comparison {
+    pub fn changed_fraction(&self) -> f32 {
+        self.changed as f32 / self.total.max(1) as f32
+    }
+
+    pub fn passes(&self, tolerance: &Tolerance) -> bool {
+        self.changed_fraction() <= tolerance.max_changed
+    }
+}
+
+/// Compares two RGBA8 images of the same size pixel by pixel, using the
+/// YIQ color difference (as in pixelmatch) so changes the eye barely notices
+/// weigh less than the raw channel values would suggest. Alpha is ignored.
+pub fn compare(expected: &[u8], actual: &[u8], tolerance: &Tolerance) -> Comparison {
+    assert_eq!(expected.len(), actual.len(), "images differ in size");
+
+    let mut comparison = Comparison {
+        changed: 0,
+        total: expected.len() / 4,
+        max_delta: 0.0,
+        diff: Vec::with_capacity(expected.len()),
+    };
+
+    for (e, a) in expected.chunks_exact(4).zip(actual.chunks_exact(4)) {
+        let delta = color_delta(e, a);
+        comparison.max_delta = comparison.max_delta.max(delta);
+
+        if delta > tolerance.threshold {
+            comparison.changed += 1;
+            comparison.diff.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let gray = 255.0 - (255.0 - luma(e)) * 0.1;
+            let gray = gray as u8;
+            comparison.diff.extend_from_slice(&[gray, gray, gray, 255]);
+        }
+    }
+
+    comparison
+}
+
+// Largest possible value of the weighted YIQ distance below.
+const MAX_YIQ_DELTA: f32 = 35215.0;
+
+fn color_delta(a: &[u8], b: &[u8]) -> f32 {
+    let (y1, i1, q1) = yiq(a);
+    let (y2, i2, q2) = yiq(b);
+    let (dy, di, dq) = (y1 - y2, i1 - i2, q1 - q2);
+    let delta = 0.5053 * dy * dy + 0.299 * di * di + 0.1957 * dq * dq;
+    (delta / MAX_YIQ_DELTA).sqrt()
+}
+
+fn yiq(pixel: &[u8]) -> (f32, f32, f32) {
+    let (r, g, b) = (pixel[0] as f32, pixel[1] as f32, pixel[2] as f32);
+    (
+        luma(pixel),
+        r * 0.595_978 - g * 0.274_176 - b * 0.321_802,
+        r * 0.211_470 - g * 0.522_617 + b * 0.311_147,
+    )
+}
+
+fn luma(pixel: &[u8]) -> f32 {
+    pixel[0] as f32 * 0.298_895 + pixel[1] as f32 * 0.586_622 + pixel[2] as f32 * 0.114_482
+}
+
+/// Reads an 8-bit RGBA PNG, as written by `write_png`.
+pub fn read_png(path: &Path) -> Result<(u32, u32, Vec<u8>), Box<dyn Error>> {
+    let decoder = png::Decoder::new(File::open(path)?);
+    let mut reader = decoder.read_info()?;
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels)?;
+
+    if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+        return Err(format!(
+            "expected 8-bit RGBA, found {:?} {:?}",
+            info.bit_depth, info.color_type
+        )
+        .into());
+    }
+    pixels.truncate(info.buffer_size());
+    Ok((info.width, info.height, pixels))
+}
+
+/// Checks a rendered RGBA8 frame against `<references>/<name>.png`. On a
+/// mismatch the frame and a diff image are written to `<output>` and the
+/// error says where. With `GLRS_BLESS` set the reference is overwritten
+/// instead.
+pub fn check(
+    name: &str,
+    references: &Path,
+    output: &Path,
+    width: u32,
+    height: u32,
+    actual: &[u8],
+    tolerance: &Tolerance,
+) -> Result<(), String> {
+    let reference = references.join(format!("{}.png", name));
+
+    if env::var_os(BLESS_VAR).is_some() {
+        fs::create_dir_all(references).map_err(|e| e.to_string())?;
+        return write_png(&reference, width, height, actual).map_err(|e| e.to_string());
+    }
+
+    let (expected_width, expected_height, expected) = read_png(&reference).map_err(|e| {
+        format!(
+            "can't read reference {}: {} (run with {}=1 to create it)",
+            reference.display(),
+            e,
+            BLESS_VAR
+        )
+    })?;
+    if (expected_width, expected_height) != (width, height) {
+        return Err(format!(
+            "{} is {}x{}, but the frame is {}x{}",
+            reference.display(),
+            expected_width,
+            expected_height,
+            width,
+            height
+        ));
+    }
+
+    let comparison = compare(&expected, actual, tolerance);
+    if comparison.passes(tolerance) {
+        return Ok(());
+    }
+
+    let actual_path = output.join(format!("{}.actual.png", name));
+    let diff_path = output.join(format!("{}.diff.png", name));
+    fs::create_dir_all(output).map_err(|e| e.to_string())?;
+    write_png(&actual_path, width, height, actual).map_err(|e| e.to_string())?;
+    write_png(&diff_path, width, height, &comparison.diff).map_err(|e| e.to_string())?;
+
+    Err(format!(
+        "{} differs from {}: {:.2}% of pixels changed (allowed {:.2}%), max delta {:.3}\n  \
+         frame: {}\n  diff:  {}",
+        name,
+        reference.display(),
+        comparison.changed_fraction() * 100.0,
+        tolerance.max_changed * 100.0,
+        comparison.max_delta,
+        actual_path.display(),
+        diff_path.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_images_match() {
+        let image = [10, 20, 30, 255, 200, 100, 50, 255];
+        let comparison = compare(&image, &image, &Tolerance::default());
+        assert_eq!(comparison.changed, 0);
+        assert_eq!(comparison.max_delta, 0.0);
+    }
+
+    #[test]
+    fn small_shifts_are_tolerated() {
+        let expected = [100, 100, 100, 255];
+        let actual = [102, 101, 99, 255];
+        let comparison = compare(&expected, &actual, &Tolerance::default());
+        assert_eq!(comparison.changed, 0);
+    }
+
+    #[test]
+    fn changed_pixels_are_red_in_the_diff() {
+        let expected = [0, 0, 0, 255, 255, 255, 255, 255];
+        let actual = [255, 255, 255, 255, 255, 255, 255, 255];
+        let comparison = compare(&expected, &actual, &Tolerance::default());
+        assert_eq!(comparison.changed, 1);
+        assert!(comparison.max_delta > 0.9);
+        assert_eq!(&comparison.diff[..4], &[255, 0, 0, 255]);
+        assert!(!comparison.passes(&Tolerance::default()));
+    }
+
+    #[test]
+    fn alpha_is_ignored() {
+        let comparison = compare(&[1, 2, 3, 0], &[1, 2, 3, 255], &Tolerance::default());
+        assert_eq!(comparison.changed, 0);
+    }
+}
@@ -0,0 +1,354 @@
+extern crate gl;
+use crate::app::Demo;
+use crate::clock::Clock;
+use crate::framebuffer::{Framebuffer, FramebufferError};
+use crate::input::Input;
+use crate::reload;
+use crate::sampler;
+use crate::timestep::FixedTimestep;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum HeadlessError {
+    /// No backend could create a context; one message per backend tried.
+    NoContext(Vec<String>),
+    Framebuffer(FramebufferError),
+    Demo(Box<dyn Error>),
+    Write {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl fmt::Display for HeadlessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeadlessError::NoContext(reasons) => {
+                writeln!(f, "failed to create a headless GL context")?;
+                for reason in reasons {
+                    writeln!(f, "  {}", reason)?;
+                }
+                Ok(())
+            }
+            HeadlessError::Framebuffer(e) => write!(f, "{}", e),
+            HeadlessError::Demo(e) => write!(f, "{}", e),
+            HeadlessError::Write { path, message } => {
+                write!(f, "failed to write {}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+impl Error for HeadlessError {}
+
+impl From<FramebufferError> for HeadlessError {
+    fn from(e: FramebufferError) -> Self {
+        HeadlessError::Framebuffer(e)
+    }
+}
+
+/// A GL context with no window. EGL's surfaceless platform is tried first
+/// (any Mesa driver, including llvmpipe), then OSMesa. Rendering has to go
+/// to a `Framebuffer`.
+pub struct HeadlessContext {
+    _backend: Backend,
+}
+
+// Only held so the context lives as long as the `HeadlessContext`.
+#[allow(dead_code)]
+enum Backend {
+    #[cfg(target_os = "linux")]
+    Egl(Box<egl::Context>),
+    #[cfg(target_os = "linux")]
+    OsMesa(glutin::Context<glutin::PossiblyCurrent>),
+}
+
+impl HeadlessContext {
+    /// Creates a context, makes it current and loads the GL functions.
+    #[cfg(target_os = "linux")]
+    pub fn new(width: u32, height: u32) -> Result<HeadlessContext, HeadlessError> {
+        use glutin::platform::unix::HeadlessContextExt;
+        use glutin::{Api, ContextBuilder, GlProfile, GlRequest};
+
+        let mut reasons = Vec::new();
+
+        match egl::Context::new() {
+            Ok(context) => {
+                gl::load_with(|symbol| context.get_proc_address(symbol));
+                sampler::forget_max_anisotropy();
+                return Ok(HeadlessContext {
+                    _backend: Backend::Egl(Box::new(context)),
+                });
+            }
+            Err(e) => reasons.push(format!("EGL: {}", e)),
+        }
+
+        let osmesa = ContextBuilder::new()
+            .with_gl(GlRequest::Specific(Api::OpenGl, (3, 3)))
+            .with_gl_profile(GlProfile::Core)
+            .build_osmesa(glutin::dpi::PhysicalSize::new(width, height));
+        match osmesa {
+            Ok(context) => match unsafe { context.make_current() } {
+                Ok(context) => {
+                    gl::load_with(|symbol| context.get_proc_address(symbol));
+                    sampler::forget_max_anisotropy();
+                    return Ok(HeadlessContext {
+                        _backend: Backend::OsMesa(context),
+                    });
+                }
+                Err((_, e)) => reasons.push(format!("OSMesa: {}", e)),
+            },
+            Err(e) => reasons.push(format!("OSMesa: {}", e)),
+        }
+
+        Err(HeadlessError::NoContext(reasons))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn new(_width: u32, _height: u32) -> Result<HeadlessContext, HeadlessError> {
+        Err(HeadlessError::NoContext(vec![
+            "headless rendering is only supported on Linux".to_string(),
+        ]))
+    }
+}
+
+pub struct RenderConfig {
+    pub width: u32,
+    pub height: u32,
+    pub frames: u32,
+    /// Simulated time between frames, in seconds.
+    pub dt: f32,
+    /// Simulation updates per second.
+    pub update_rate: f32,
+    /// Seed for the RNG handed to the demo.
+    pub seed: u64,
+    /// Makes every `ShaderFile` the demo creates built-in only, so files
+    /// under `shaders/` don't change the frames.
+    pub builtin_shaders: bool,
+}
+
+/// Runs a demo offscreen for `config.frames` frames and hands each one to
+/// `on_frame` as RGBA8 rows, top row first.
+pub fn run(
+    config: &RenderConfig,
+    create: impl FnOnce(StdRng) -> Result<Box<dyn Demo>, Box<dyn Error>>,
+    mut on_frame: impl FnMut(u32, Vec<u8>) -> Result<(), HeadlessError>,
+) -> Result<(), HeadlessError> {
+    let _context = HeadlessContext::new(config.width, config.height)?;
+    let _builtin = reload::builtin_shaders_on_this_thread(config.builtin_shaders);
+    let framebuffer = Framebuffer::new(config.width as i32, config.height as i32)?;
+
+    let mut demo = create(StdRng::seed_from_u64(config.seed)).map_err(HeadlessError::Demo)?;
+    framebuffer.bind();
+    demo.init(config.width, config.height);
+
+    let mut clock = Clock::fixed(config.dt);
+    let mut timestep = FixedTimestep::new(config.update_rate);
+    // Nobody is at the keyboard.
+    let input = Input::new(config.width, config.height);
+    for frame in 0..config.frames {
+        for _ in 0..timestep.advance(clock.tick()) {
+            demo.update(timestep.step(), &input);
+        }
+        framebuffer.bind();
+        demo.render(timestep.alpha());
+
+        let mut pixels = framebuffer.read_pixels();
+        // Windows have no alpha channel, so keep what one would have shown.
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel[3] = 255;
+        }
+        on_frame(frame, pixels)?;
+    }
+
+    demo.destroy();
+    Ok(())
+}
+
+/// Runs a demo offscreen and writes each frame to `out` as
+/// `frame_0000.png`, `frame_0001.png` and so on.
+pub fn render(
+    config: &RenderConfig,
+    out: &Path,
+    create: impl FnOnce(StdRng) -> Result<Box<dyn Demo>, Box<dyn Error>>,
+) -> Result<(), HeadlessError> {
+    let write_error = |path: &Path, e: &dyn Error| HeadlessError::Write {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    };
+
+    fs::create_dir_all(out).map_err(|e| write_error(out, &e))?;
+
+    run(config, create, |frame, pixels| {
+        let path = out.join(format!("frame_{:04}.png", frame));
+        write_png(&path, config.width, config.height, &pixels)
+            .map_err(|e| write_error(&path, e.as_ref()))
+    })
+}
+
+/// Writes tightly packed RGBA8 rows, top row first, as a PNG.
+pub fn write_png(path: &Path, width: u32, height: u32, rgba: &[u8]) -> Result<(), Box<dyn Error>> {
+    let file = File::create(path)?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(rgba)?;
+    Ok(())
+}
+
+// For unit tests that draw. The GL function pointers are process-wide, so
+// only one test holds a context at a time. Like the golden tests, these fail
+// without a context unless `GLRS_SKIP_GOLDEN` is set, and then get `None`.
+#[cfg(test)]
+pub(crate) fn test_context() -> Option<(HeadlessContext, std::sync::MutexGuard<'static, ()>)> {
+    static GL: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    let guard = GL.lock().unwrap_or_else(|e| e.into_inner());
+    match HeadlessContext::new(64, 64) {
+        Ok(context) => Some((context, guard)),
+        Err(e) if std::env::var_os("GLRS_SKIP_GOLDEN").is_some() => {
+            eprintln!("skipping: {}", e);
+            None
+        }
+        Err(e) => panic!("{}set GLRS_SKIP_GOLDEN=1 to skip the GL tests", e),
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod egl {
+    use glutin_egl_sys::egl;
+    use glutin_egl_sys::egl::types::*;
+    use libloading::Library;
+    use std::ffi::{c_void, CString};
+    use std::ptr;
+
+    // From EGL_MESA_platform_surfaceless, which the bindings don't include.
+    const PLATFORM_SURFACELESS_MESA: EGLenum = 0x31DD;
+
+    /// An OpenGL 3.3 core context on Mesa's surfaceless platform, current on
+    /// the thread that created it.
+    pub struct Context {
+        egl: egl::Egl,
+        display: EGLDisplay,
+        context: EGLContext,
+        // Holds the function pointers in `egl` valid.
+        _library: Library,
+    }
+
+    impl Context {
+        pub fn new() -> Result<Context, String> {
+            let library = unsafe { Library::new("libEGL.so.1") }.map_err(|e| e.to_string())?;
+            let egl = egl::Egl::load_with(|name| {
+                let symbol = CString::new(name).unwrap();
+                unsafe {
+                    library
+                        .get::<*const c_void>(symbol.as_bytes_with_nul())
+                        .map(|s| *s)
+                        .unwrap_or(ptr::null())
+                }
+            });
+
+            unsafe {
+                if !egl.GetPlatformDisplay.is_loaded() {
+                    return Err("eglGetPlatformDisplay is not available".to_string());
+                }
+                let display = egl.GetPlatformDisplay(
+                    PLATFORM_SURFACELESS_MESA,
+                    egl::DEFAULT_DISPLAY as *mut c_void,
+                    ptr::null(),
+                );
+                if display == egl::NO_DISPLAY {
+                    return Err("no surfaceless display".to_string());
+                }
+                if egl.Initialize(display, ptr::null_mut(), ptr::null_mut()) == 0 {
+                    return Err(format!("eglInitialize failed (0x{:x})", egl.GetError()));
+                }
+
+                let context = match create_context(&egl, display) {
+                    Ok(context) => context,
+                    Err(e) => {
+                        egl.Terminate(display);
+                        return Err(e);
+                    }
+                };
+
+                if egl.MakeCurrent(display, egl::NO_SURFACE, egl::NO_SURFACE, context) == 0 {
+                    let error = egl.GetError();
+                    egl.DestroyContext(display, context);
+                    egl.Terminate(display);
+                    return Err(format!("eglMakeCurrent failed (0x{:x})", error));
+                }
+
+                Ok(Context {
+                    egl,
+                    display,
+                    context,
+                    _library: library,
+                })
+            }
+        }
+
+        pub fn get_proc_address(&self, name: &str) -> *const c_void {
+            let name = CString::new(name).unwrap();
+            unsafe { self.egl.GetProcAddress(name.as_ptr()) as *const c_void }
+        }
+    }
+
+    unsafe fn create_context(egl: &egl::Egl, display: EGLDisplay) -> Result<EGLContext, String> {
+        if egl.BindAPI(egl::OPENGL_API) == 0 {
+            return Err("desktop OpenGL is not supported".to_string());
+        }
+
+        // No surface is ever created, so don't ask for window support.
+        let config_attribs = [
+            egl::SURFACE_TYPE as EGLint,
+            0,
+            egl::RENDERABLE_TYPE as EGLint,
+            egl::OPENGL_BIT as EGLint,
+            egl::NONE as EGLint,
+        ];
+        let mut config = ptr::null();
+        let mut count = 0;
+        if egl.ChooseConfig(display, config_attribs.as_ptr(), &mut config, 1, &mut count) == 0
+            || count == 0
+        {
+            return Err("no OpenGL config".to_string());
+        }
+
+        let context_attribs = [
+            egl::CONTEXT_MAJOR_VERSION as EGLint,
+            3,
+            egl::CONTEXT_MINOR_VERSION as EGLint,
+            3,
+            egl::CONTEXT_OPENGL_PROFILE_MASK as EGLint,
+            egl::CONTEXT_OPENGL_CORE_PROFILE_BIT as EGLint,
+            egl::NONE as EGLint,
+        ];
+        let context = egl.CreateContext(display, config, egl::NO_CONTEXT, context_attribs.as_ptr());
+        if context.is_null() {
+            return Err(format!("eglCreateContext failed (0x{:x})", egl.GetError()));
+        }
+        Ok(context)
+    }
+
+    impl Drop for Context {
+        fn drop(&mut self) {
+            unsafe {
+                self.egl.MakeCurrent(
+                    self.display,
+                    egl::NO_SURFACE,
+                    egl::NO_SURFACE,
+                    egl::NO_CONTEXT,
+                );
+                self.egl.DestroyContext(self.display, self.context);
+                self.egl.Terminate(self.display);
+            }
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use glutin::event::{ElementState, KeyboardInput, MouseScrollDelta, WindowEvent};
+use std::collections::HashSet;
+
+pub use glutin::event::{MouseButton, VirtualKeyCode};
+
+// Pixels of touchpad scrolling that count as one line of a mouse wheel.
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// Mouse and keyboard state built up from window events, for demos to query
+/// during `update`. "Pressed" and "released" cover everything since the
+/// previous update, so a click between two updates is seen exactly once.
+#[derive(Debug, Clone, Default)]
+pub struct Input {
+    width: u32,
+    height: u32,
+    cursor: Option<[f32; 2]>,
+    buttons: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+    scroll: f32,
+    keys: HashSet<VirtualKeyCode>,
+    keys_pressed: HashSet<VirtualKeyCode>,
+    keys_released: HashSet<VirtualKeyCode>,
+}
+
+impl Input {
+    pub fn new(width: u32, height: u32) -> Input {
+        Input {
+            width,
+            height,
+            ..Input::default()
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        match *event {
+            WindowEvent::Resized(size) => {
+                self.width = size.width;
+                self.height = size.height;
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor = Some([position.x as f32, position.y as f32]);
+            }
+            WindowEvent::CursorLeft { .. } => self.cursor = None,
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed => {
+                    self.buttons.insert(button);
+                    self.buttons_pressed.insert(button);
+                }
+                ElementState::Released => {
+                    self.buttons.remove(&button);
+                    self.buttons_released.insert(button);
+                }
+            },
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.scroll += match delta {
+                    MouseScrollDelta::LineDelta(_, y) => y,
+                    MouseScrollDelta::PixelDelta(position) => position.y as f32 / PIXELS_PER_LINE,
+                };
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        virtual_keycode: Some(key),
+                        state,
+                        ..
+                    },
+                ..
+            } => match state {
+                // Key repeat sends presses without releases in between.
+                ElementState::Pressed => {
+                    if self.keys.insert(key) {
+                        self.keys_pressed.insert(key);
+                    }
+                }
+                ElementState::Released => {
+                    self.keys.remove(&key);
+                    self.keys_released.insert(key);
+                }
+            },
+            WindowEvent::Focused(false) => {
+                self.buttons.clear();
+                self.keys.clear();
+            }
+            _ => {}
+        }
+    }
+
+    /// Forgets presses, releases and scrolling once an update has seen them.
+    pub fn end_update(&mut self) {
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+        self.scroll = 0.0;
+    }
+
+    /// Window size in physical pixels.
+    pub fn window_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Cursor position in physical pixels from the top left, or `None` while
+    /// it is outside the window.
+    pub fn cursor(&self) -> Option<[f32; 2]> {
+        self.cursor
+    }
+
+    /// Cursor position in clip space, from -1 to 1 with y up.
+    pub fn cursor_clip(&self) -> Option<[f32; 2]> {
+        let [x, y] = self.cursor?;
+        let (width, height) = (self.width.max(1) as f32, self.height.max(1) as f32);
+        Some([x / width * 2.0 - 1.0, 1.0 - y / height * 2.0])
+    }
+
+    pub fn button_down(&self, button: MouseButton) -> bool {
+        self.buttons.contains(&button)
+    }
+
+    pub fn button_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    pub fn button_released(&self, button: MouseButton) -> bool {
+        self.buttons_released.contains(&button)
+    }
+
+    /// Wheel movement in lines, positive away from the user.
+    pub fn scroll(&self) -> f32 {
+        self.scroll
+    }
+
+    pub fn key_down(&self, key: VirtualKeyCode) -> bool {
+        self.keys.contains(&key)
+    }
+
+    pub fn key_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    pub fn key_released(&self, key: VirtualKeyCode) -> bool {
+        self.keys_released.contains(&key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glutin::dpi::PhysicalPosition;
+    use glutin::event::DeviceId;
+
+    #[allow(deprecated)]
+    fn click(state: ElementState) -> WindowEvent<'static> {
+        WindowEvent::MouseInput {
+            device_id: unsafe { DeviceId::dummy() },
+            state,
+            button: MouseButton::Left,
+            modifiers: Default::default(),
+        }
+    }
+
+    #[test]
+    fn presses_last_until_an_update_sees_them() {
+        let mut input = Input::new(100, 100);
+        input.handle_event(&click(ElementState::Pressed));
+        input.handle_event(&click(ElementState::Released));
+        assert!(input.button_pressed(MouseButton::Left));
+        assert!(input.button_released(MouseButton::Left));
+        assert!(!input.button_down(MouseButton::Left));
+
+        input.end_update();
+        assert!(!input.button_pressed(MouseButton::Left));
+    }
+
+    #[allow(deprecated)]
+    #[test]
+    fn cursor_maps_to_clip_space() {
+        let mut input = Input::new(200, 100);
+        assert_eq!(input.cursor_clip(), None);
+        input.handle_event(&WindowEvent::CursorMoved {
+            device_id: unsafe { DeviceId::dummy() },
+            position: PhysicalPosition::new(50.0, 25.0),
+            modifiers: Default::default(),
+        });
+        assert_eq!(input.cursor(), Some([50.0, 25.0]));
+        assert_eq!(input.cursor_clip(), Some([-0.5, 0.5]));
+    }
+}
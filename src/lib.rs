@@ -0,0 +1,23 @@
+// Lets the `::glrs` paths that `#[derive(VertexLayout)]` emits resolve here too.
+extern crate self as glrs;
+
+pub mod app;
+pub mod buffer;
+pub mod clock;
+pub mod framebuffer;
+pub mod golden;
+pub mod headless;
+pub mod input;
+pub mod math;
+pub mod particles;
+pub mod playlist;
+pub mod preprocess;
+pub mod program;
+pub mod reload;
+pub mod sampler;
+pub mod shader;
+pub mod shadertoy;
+pub mod texture;
+pub mod timestep;
+pub mod vertex_array;
+pub mod window;
@@ -1,7 +1,393 @@
-#![allow(dead_code)]
-mod texture;
-mod utils;
+mod demos;
+
+use glrs::app::{self, Demo, Screensaver, WindowConfig};
+use glrs::headless::{self, RenderConfig};
+use glrs::playlist::{Playlist, Transition};
+use glrs::shadertoy::{self, Shadertoy};
+use glrs::window::{self, VideoModeRequest, WindowMode};
+use glutin::event_loop::EventLoop;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+static USAGE: &str = "usage: glrs <command>
+
+commands:
+    list                     list the available demos
+    monitors                 list the monitors and their video modes
+    run <demos> [options]    run a demo
+    render <demos> [options] render a demo offscreen to PNG frames
+    shadertoy <file> [options]
+                             run a Shadertoy shader: a GLSL file defining
+                             mainImage(out vec4, in vec2), reloaded on save
+
+    <demos> is a demo name, or a playlist of them separated by commas, each
+    optionally followed by how many seconds it plays: particles02:30,texture
+
+options:
+    --seed <n>               seed the demo's RNG (default: random for run, 0 for render)
+    --fixed-dt <seconds>     step the simulation by a fixed time every frame
+                             (default: real time for run, 1/60 for render)
+    --update-rate <hz>       simulation updates per second (default 60)
+    --cycle <seconds>        time each playlist demo plays by default (default 60)
+    --transition <name>      cut, crossfade, wipe, dissolve, or a GLSL file in the
+                             gl-transitions format (default crossfade)
+    --transition-time <s>    length of the transitions (default 2)
+
+run options:
+    --no-vsync               don't wait for the display between frames
+    --max-fps <n>            frame rate cap when vsync is off
+    --window <mode>          windowed, borderless, fullscreen or span (default windowed)
+    --monitor <n>            monitor for borderless or fullscreen (default: current)
+    --video-mode <WxH[@hz]>  video mode for fullscreen (default: the largest)
+    --screensaver            go borderless fullscreen, hide the cursor and exit
+                             on any key, click or mouse movement
+
+run keys:
+    space                    pause or resume
+    . or right arrow         step once while paused
+    r                        restart the demo with the same seed
+    f11                      toggle between windowed and fullscreen
+
+shadertoy options:
+    the run options without the playlist ones, and
+    --channel<n> <image>     image for iChannel0 to iChannel3
+
+render options:
+    --frames <n>             number of frames to write (default 1)
+    --size <width>x<height>  frame size (default: the demo's window size)
+    --out <dir>              output directory (default frames/)";
+
+const RUN_OPTIONS: &[&str] = &[
+    "--seed",
+    "--fixed-dt",
+    "--update-rate",
+    "--no-vsync",
+    "--max-fps",
+    "--window",
+    "--monitor",
+    "--video-mode",
+    "--screensaver",
+    "--cycle",
+    "--transition",
+    "--transition-time",
+];
+const SHADERTOY_OPTIONS: &[&str] = &[
+    "--seed",
+    "--fixed-dt",
+    "--update-rate",
+    "--no-vsync",
+    "--max-fps",
+    "--window",
+    "--monitor",
+    "--video-mode",
+    "--screensaver",
+    "--channel0",
+    "--channel1",
+    "--channel2",
+    "--channel3",
+];
+const RENDER_OPTIONS: &[&str] = &[
+    "--seed",
+    "--fixed-dt",
+    "--update-rate",
+    "--frames",
+    "--size",
+    "--out",
+    "--cycle",
+    "--transition",
+    "--transition-time",
+];
+
+/// Options that don't take a value.
+const FLAGS: &[&str] = &["--no-vsync", "--screensaver"];
+
+const DEFAULT_CYCLE: f32 = 60.0;
+const DEFAULT_TRANSITION_TIME: f32 = 2.0;
 
 fn main() {
-    texture::main();
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["list"] => {
+            for demo in demos::DEMOS {
+                println!("{:<12} {}", demo.name, demo.description);
+            }
+        }
+        ["monitors"] => list_monitors(),
+        ["run", demos, options @ ..] => {
+            let options = parse_options(options, RUN_OPTIONS);
+            let (demo, playlist) = parse_playlist(demos, &options);
+
+            let config = options.window_config(demo.window_config());
+            match playlist {
+                Some(playlist) => app::run_playlist(config, playlist),
+                None => app::run(config, demo.create),
+            }
+        }
+        ["shadertoy", file, options @ ..] => {
+            let options = parse_options(options, SHADERTOY_OPTIONS);
+            let config = options.window_config(WindowConfig::default());
+            let file = PathBuf::from(file);
+            let channels = options.channels;
+            app::run_with(config, move |_| {
+                Ok(Box::new(Shadertoy::new(&file, &channels)?) as Box<dyn Demo>)
+            })
+        }
+        ["render", demos, options @ ..] => {
+            let options = parse_options(options, RENDER_OPTIONS);
+            let (demo, playlist) = parse_playlist(demos, &options);
+
+            let (width, height) = options.size.unwrap_or((demo.width, demo.height));
+            let config = RenderConfig {
+                width,
+                height,
+                frames: options.frames.unwrap_or(1),
+                dt: options.fixed_dt.unwrap_or(1.0 / 60.0),
+                update_rate: options.update_rate.unwrap_or(app::DEFAULT_UPDATE_RATE),
+                seed: options.seed.unwrap_or(0),
+                builtin_shaders: false,
+            };
+            let out = options.out.unwrap_or_else(|| PathBuf::from("frames"));
+
+            let result = match playlist {
+                Some(playlist) => headless::render(&config, &out, |rng| {
+                    Ok(Box::new(playlist.start(rng)?) as Box<dyn Demo>)
+                }),
+                None => headless::render(&config, &out, demo.create),
+            };
+            if let Err(e) = result {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+            eprintln!("wrote {} frame(s) to {}", config.frames, out.display());
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+fn find_demo(name: &str) -> &'static demos::DemoInfo {
+    match demos::find(name) {
+        Some(demo) => demo,
+        None => {
+            eprintln!("unknown demo `{}`, see `glrs list`", name);
+            process::exit(1);
+        }
+    }
+}
+
+/// The first demo of `demos`, and the whole playlist when there are more.
+fn parse_playlist(demos: &str, options: &Options) -> (&'static demos::DemoInfo, Option<Playlist>) {
+    let mut playlist = Playlist::new().transition(
+        options.transition.clone().unwrap_or(Transition::Crossfade),
+        options.transition_time.unwrap_or(DEFAULT_TRANSITION_TIME),
+    );
+    let mut first = None;
+    for entry in demos.split(',') {
+        let (name, duration) = match entry.split_once(':') {
+            Some((name, seconds)) => match seconds.parse::<f32>() {
+                Ok(duration) if duration.is_finite() && duration > 0.0 => (name, duration),
+                _ => {
+                    eprintln!("invalid play time `{}`\n\n{}", seconds, USAGE);
+                    process::exit(2);
+                }
+            },
+            None => (entry, options.cycle.unwrap_or(DEFAULT_CYCLE)),
+        };
+        let demo = find_demo(name);
+        first.get_or_insert(demo);
+        playlist = playlist.add(demo.create, duration);
+    }
+
+    let first = first.expect("split always yields an entry");
+    (first, Some(playlist).filter(|playlist| playlist.len() > 1))
+}
+
+#[derive(Default)]
+struct Options {
+    seed: Option<u64>,
+    fixed_dt: Option<f32>,
+    frames: Option<u32>,
+    size: Option<(u32, u32)>,
+    out: Option<PathBuf>,
+    update_rate: Option<f32>,
+    no_vsync: bool,
+    max_fps: Option<f32>,
+    cycle: Option<f32>,
+    transition: Option<Transition>,
+    transition_time: Option<f32>,
+    screensaver: bool,
+    window: Option<WindowMode>,
+    monitor: Option<usize>,
+    video_mode: Option<VideoModeRequest>,
+    channels: [Option<PathBuf>; shadertoy::CHANNELS],
+}
+
+impl Options {
+    /// `config` with the run options applied.
+    fn window_config(&self, mut config: WindowConfig) -> WindowConfig {
+        config.seed = self.seed;
+        config.fixed_dt = self.fixed_dt;
+        config.update_rate = self.update_rate.unwrap_or(config.update_rate);
+        config.vsync = !self.no_vsync;
+        config.max_fps = self.max_fps;
+        config.mode = self.window_mode();
+        if self.screensaver {
+            config.screensaver = Some(Screensaver::default());
+        }
+        config
+    }
+
+    /// The `--window` mode with the chosen monitor and video mode, exiting
+    /// with the usage text if the mode doesn't take them. Screensavers
+    /// default to borderless.
+    fn window_mode(&self) -> WindowMode {
+        let default = match self.screensaver {
+            true => WindowMode::Borderless { monitor: None },
+            false => WindowMode::Windowed,
+        };
+        match (
+            self.window.unwrap_or(default),
+            self.monitor,
+            self.video_mode,
+        ) {
+            (WindowMode::Borderless { .. }, monitor, None) => WindowMode::Borderless { monitor },
+            (WindowMode::Exclusive { .. }, monitor, video_mode) => WindowMode::Exclusive {
+                monitor,
+                video_mode,
+            },
+            (mode, None, None) => mode,
+            (_, _, Some(_)) => {
+                eprintln!("--video-mode needs `--window fullscreen`\n\n{}", USAGE);
+                process::exit(2);
+            }
+            (_, Some(_), None) => {
+                eprintln!(
+                    "--monitor needs `--window borderless` or `--window fullscreen`\n\n{}",
+                    USAGE
+                );
+                process::exit(2);
+            }
+        }
+    }
+}
+
+fn list_monitors() {
+    let el = EventLoop::new();
+    for (i, monitor) in el.available_monitors().enumerate() {
+        let (size, position) = (monitor.size(), monitor.position());
+        println!(
+            "{}: {} {}x{} at {},{} scale {}",
+            i,
+            window::monitor_name(&monitor),
+            size.width,
+            size.height,
+            position.x,
+            position.y,
+            monitor.scale_factor()
+        );
+        for mode in monitor.video_modes() {
+            let size = mode.size();
+            println!(
+                "    {}x{}@{} {}-bit",
+                size.width,
+                size.height,
+                mode.refresh_rate(),
+                mode.bit_depth()
+            );
+        }
+    }
+}
+
+/// Parses `--name value` pairs and flags, exiting with the usage text on anything
+/// not in `allowed` or a value that doesn't parse.
+fn parse_options(args: &[&str], allowed: &[&str]) -> Options {
+    match try_parse_options(args, allowed) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+fn try_parse_options(args: &[&str], allowed: &[&str]) -> Result<Options, String> {
+    let mut options = Options::default();
+
+    let mut args = args.iter();
+    while let Some(&name) = args.next() {
+        if !allowed.contains(&name) {
+            return Err(format!("unknown option `{}`", name));
+        }
+        if FLAGS.contains(&name) {
+            match name {
+                "--no-vsync" => options.no_vsync = true,
+                "--screensaver" => options.screensaver = true,
+                _ => unreachable!(),
+            }
+            continue;
+        }
+
+        let value = *args
+            .next()
+            .ok_or_else(|| format!("missing value for {}", name))?;
+        let invalid = |what: &str| format!("invalid {} `{}`", what, value);
+        let positive = |what: &str| {
+            value
+                .parse::<f32>()
+                .ok()
+                .filter(|v| v.is_finite() && *v > 0.0)
+                .ok_or_else(|| invalid(what))
+        };
+
+        match name {
+            "--seed" => options.seed = Some(value.parse().map_err(|_| invalid("seed"))?),
+            "--fixed-dt" => options.fixed_dt = Some(positive("time step")?),
+            "--update-rate" => options.update_rate = Some(positive("update rate")?),
+            "--max-fps" => options.max_fps = Some(positive("frame rate")?),
+            "--cycle" => options.cycle = Some(positive("cycle time")?),
+            "--transition" => {
+                let transition = match value.parse() {
+                    Ok(transition) => transition,
+                    Err(_) => Transition::Custom(fs::read_to_string(value).map_err(|e| {
+                        format!(
+                            "invalid transition `{}`, not a built-in one or a readable file: {}",
+                            value, e
+                        )
+                    })?),
+                };
+                options.transition = Some(transition);
+            }
+            "--transition-time" => options.transition_time = Some(positive("transition time")?),
+            "--frames" => options.frames = Some(value.parse().map_err(|_| invalid("frame count"))?),
+            "--size" => {
+                let size = value
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                    .filter(|&(w, h)| w > 0 && h > 0)
+                    .ok_or_else(|| format!("invalid size `{}`, expected WIDTHxHEIGHT", value))?;
+                options.size = Some(size);
+            }
+            "--out" => options.out = Some(PathBuf::from(value)),
+            "--window" => options.window = Some(value.parse()?),
+            "--monitor" => options.monitor = Some(value.parse().map_err(|_| invalid("monitor"))?),
+            "--video-mode" => options.video_mode = Some(value.parse()?),
+            "--channel0" => options.channels[0] = Some(PathBuf::from(value)),
+            "--channel1" => options.channels[1] = Some(PathBuf::from(value)),
+            "--channel2" => options.channels[2] = Some(PathBuf::from(value)),
+            "--channel3" => options.channels[3] = Some(PathBuf::from(value)),
+            _ => unreachable!(),
+        }
+    }
+
+    Ok(options)
 }
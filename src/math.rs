@@ -0,0 +1,11 @@
+pub fn lerp(norm: f32, min: f32, max: f32) -> f32 {
+    (max - min) * norm + min
+}
+
+pub fn map(val: f32, srcmin: f32, srcmax: f32, dstmin: f32, dstmax: f32) -> f32 {
+    lerp(norm(val, srcmin, srcmax), dstmin, dstmax)
+}
+
+pub fn norm(val: f32, min: f32, max: f32) -> f32 {
+    (val - min) / (max - min)
+}
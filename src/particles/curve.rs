@@ -0,0 +1,302 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseCurveError {
+    pub message: String,
+}
+
+impl fmt::Display for ParseCurveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid curve: {}", self.message)
+    }
+}
+
+impl Error for ParseCurveError {}
+
+fn parse_error(message: String) -> ParseCurveError {
+    ParseCurveError { message }
+}
+
+/// A value that changes over a particle's life, given as keys at normalized
+/// ages from 0 (just spawned) to 1 (about to die) and interpolated linearly
+/// between them. Before the first key and after the last the nearest key's
+/// value holds.
+///
+/// Parses from keys like `"0:1 0.5:2 1:0"`, or a single number for a
+/// constant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Curve {
+    keys: Vec<(f32, f32)>,
+}
+
+impl Curve {
+    /// Keys may come in any order. An empty curve is a constant 1.
+    pub fn new(mut keys: Vec<(f32, f32)>) -> Curve {
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Curve { keys }
+    }
+
+    pub fn constant(value: f32) -> Curve {
+        Curve::new(vec![(0.0, value)])
+    }
+
+    /// From `from` at birth to `to` at death.
+    pub fn linear(from: f32, to: f32) -> Curve {
+        Curve::new(vec![(0.0, from), (1.0, to)])
+    }
+
+    pub fn keys(&self) -> &[(f32, f32)] {
+        &self.keys
+    }
+
+    pub fn evaluate(&self, t: f32) -> f32 {
+        evaluate(&self.keys, t, 1.0, |a, b, t| a + (b - a) * t)
+    }
+}
+
+impl Default for Curve {
+    fn default() -> Self {
+        Curve::constant(1.0)
+    }
+}
+
+impl FromStr for Curve {
+    type Err = ParseCurveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(value) = s.trim().parse() {
+            return Ok(Curve::constant(value));
+        }
+        let keys = parse_keys(s, |value| {
+            value
+                .parse()
+                .map_err(|_| parse_error(format!("`{}` is not a number", value)))
+        })?;
+        Ok(Curve::new(keys))
+    }
+}
+
+/// An RGBA color over a particle's life, keyed like a `Curve`.
+///
+/// Parses from keys with hex colors, like `"0:#ffffff 0.6:#ff8000 1:#40000000"`;
+/// colors without an alpha byte are opaque.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    keys: Vec<(f32, [f32; 4])>,
+}
+
+impl Gradient {
+    /// Keys may come in any order. An empty gradient is opaque white.
+    pub fn new(mut keys: Vec<(f32, [f32; 4])>) -> Gradient {
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Gradient { keys }
+    }
+
+    pub fn constant(color: [f32; 4]) -> Gradient {
+        Gradient::new(vec![(0.0, color)])
+    }
+
+    pub fn keys(&self) -> &[(f32, [f32; 4])] {
+        &self.keys
+    }
+
+    pub fn evaluate(&self, t: f32) -> [f32; 4] {
+        evaluate(&self.keys, t, [1.0; 4], |a, b, t| {
+            let mut color = a;
+            for (c, b) in color.iter_mut().zip(b) {
+                *c += (b - *c) * t;
+            }
+            color
+        })
+    }
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Gradient::constant([1.0; 4])
+    }
+}
+
+impl FromStr for Gradient {
+    type Err = ParseCurveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Gradient::new(parse_keys(s, parse_color)?))
+    }
+}
+
+fn evaluate<T: Copy>(keys: &[(f32, T)], t: f32, empty: T, lerp: impl Fn(T, T, f32) -> T) -> T {
+    let (first, last) = match (keys.first(), keys.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return empty,
+    };
+    if t <= first.0 {
+        return first.1;
+    }
+    if t >= last.0 {
+        return last.1;
+    }
+
+    let next = keys.partition_point(|&(key, _)| key <= t);
+    let (t0, a) = keys[next - 1];
+    let (t1, b) = keys[next];
+    lerp(a, b, (t - t0) / (t1 - t0))
+}
+
+fn parse_keys<T>(
+    s: &str,
+    parse_value: impl Fn(&str) -> Result<T, ParseCurveError>,
+) -> Result<Vec<(f32, T)>, ParseCurveError> {
+    s.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|key| !key.is_empty())
+        .map(|key| {
+            let (t, value) = key
+                .split_once(':')
+                .ok_or_else(|| parse_error(format!("expected AGE:VALUE, found `{}`", key)))?;
+            let t = t
+                .parse::<f32>()
+                .ok()
+                .filter(|t| (0.0..=1.0).contains(t))
+                .ok_or_else(|| parse_error(format!("age `{}` is not between 0 and 1", t)))?;
+            Ok((t, parse_value(value)?))
+        })
+        .collect()
+}
+
+fn parse_color(value: &str) -> Result<[f32; 4], ParseCurveError> {
+    let invalid = || parse_error(format!("expected #RRGGBB or #RRGGBBAA, found `{}`", value));
+    let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+        return Err(invalid());
+    }
+
+    let mut color = [1.0; 4];
+    for (i, c) in color.iter_mut().take(hex.len() / 2).enumerate() {
+        let byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+        *c = byte as f32 / 255.0;
+    }
+    Ok(color)
+}
+
+/// Fades alpha in after spawning and out before dying, over fractions of
+/// the particle's life.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Fade {
+    pub fade_in: f32,
+    pub fade_out: f32,
+}
+
+impl Fade {
+    pub fn new(fade_in: f32, fade_out: f32) -> Fade {
+        Fade { fade_in, fade_out }
+    }
+
+    pub fn evaluate(&self, t: f32) -> f32 {
+        let mut alpha: f32 = 1.0;
+        if self.fade_in > 0.0 {
+            alpha = alpha.min(t / self.fade_in);
+        }
+        if self.fade_out > 0.0 {
+            alpha = alpha.min((1.0 - t) / self.fade_out);
+        }
+        alpha.clamp(0.0, 1.0)
+    }
+}
+
+/// How a particle system's particles look over their lives. The size curve
+/// multiplies each particle's own size, and the fade multiplies the
+/// gradient's alpha. Particles that live forever stay at age 0.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LifeCurves {
+    pub size: Curve,
+    pub color: Gradient,
+    pub fade: Fade,
+}
+
+impl LifeCurves {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn size(mut self, size: Curve) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn color(mut self, color: Gradient) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn fade(mut self, fade_in: f32, fade_out: f32) -> Self {
+        self.fade = Fade::new(fade_in, fade_out);
+        self
+    }
+
+    /// The color and alpha at normalized age `t`.
+    pub fn color_at(&self, t: f32) -> [f32; 4] {
+        let mut color = self.color.evaluate(t);
+        color[3] *= self.fade.evaluate(t);
+        color
+    }
+
+    pub fn size_at(&self, t: f32) -> f32 {
+        self.size.evaluate(t)
+    }
+
+    /// Samples the curves at `resolution` evenly spaced ages from 0 to 1, as
+    /// two RGBA rows: the color, then the size in red.
+    pub fn bake(&self, resolution: usize) -> Vec<[f32; 4]> {
+        let resolution = resolution.max(2);
+        let ages = (0..resolution).map(|i| i as f32 / (resolution - 1) as f32);
+        let colors = ages.clone().map(|t| self.color_at(t));
+        let sizes = ages.map(|t| [self.size_at(t), 0.0, 0.0, 0.0]);
+        colors.chain(sizes).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curves_interpolate_between_keys() {
+        let curve: Curve = "0:1 0.5:3, 1:0".parse().unwrap();
+        assert_eq!(curve.evaluate(-1.0), 1.0);
+        assert_eq!(curve.evaluate(0.25), 2.0);
+        assert_eq!(curve.evaluate(0.75), 1.5);
+        assert_eq!(curve.evaluate(2.0), 0.0);
+        assert_eq!("4".parse::<Curve>().unwrap().evaluate(0.3), 4.0);
+    }
+
+    #[test]
+    fn gradients_parse_hex_colors() {
+        let gradient: Gradient = "1:#00000000 0:#ff0000".parse().unwrap();
+        assert_eq!(gradient.evaluate(0.0), [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(gradient.evaluate(0.5), [0.5, 0.0, 0.0, 0.5]);
+        assert!("0:red".parse::<Gradient>().is_err());
+        assert!("2:#ffffff".parse::<Gradient>().is_err());
+    }
+
+    #[test]
+    fn fade_ramps_alpha_at_both_ends() {
+        let curves = LifeCurves::new().fade(0.1, 0.5);
+        assert_eq!(curves.color_at(0.0)[3], 0.0);
+        assert!((curves.color_at(0.05)[3] - 0.5).abs() < 1e-6);
+        assert_eq!(curves.color_at(0.3)[3], 1.0);
+        assert!((curves.color_at(0.75)[3] - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn bake_writes_colors_then_sizes() {
+        let curves = LifeCurves::new().size(Curve::linear(2.0, 0.0));
+        let baked = curves.bake(3);
+        assert_eq!(baked.len(), 6);
+        assert_eq!(baked[0], [1.0; 4]);
+        assert_eq!(baked[3][0], 2.0);
+        assert_eq!(baked[4][0], 1.0);
+        assert_eq!(baked[5][0], 0.0);
+    }
+}
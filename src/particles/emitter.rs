@@ -0,0 +1,496 @@
+use super::{Emitter, Particle, Particles};
+use crate::texture::TextureError;
+use rand::rngs::StdRng;
+use rand::Rng;
+use stb_image::image::{load, LoadResult};
+use std::f32::consts::PI;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+/// Where in the xy plane an emitter spawns particles, uniformly over the
+/// shape's length or area.
+#[derive(Debug, Clone)]
+pub enum Shape {
+    Point([f32; 2]),
+    Line([f32; 2], [f32; 2]),
+    /// A filled disc.
+    Circle {
+        center: [f32; 2],
+        radius: f32,
+    },
+    Rect {
+        min: [f32; 2],
+        max: [f32; 2],
+    },
+    /// The area between two circles; equal radii give just the outline.
+    Ring {
+        center: [f32; 2],
+        inner: f32,
+        outer: f32,
+    },
+    Polyline(Polyline),
+    /// The opaque pixels of an image, stretched over `min..max`.
+    Mask {
+        mask: ImageMask,
+        min: [f32; 2],
+        max: [f32; 2],
+    },
+}
+
+/// Connected line segments, sampled by length so long segments get more
+/// particles than short ones.
+#[derive(Debug, Clone)]
+pub struct Polyline {
+    points: Vec<[f32; 2]>,
+    // Distance along the line to the end of each segment.
+    lengths: Vec<f32>,
+}
+
+impl Polyline {
+    pub fn new(points: Vec<[f32; 2]>) -> Polyline {
+        let mut total = 0.0;
+        let lengths = points
+            .windows(2)
+            .map(|segment| {
+                total += distance(segment[0], segment[1]);
+                total
+            })
+            .collect();
+        Polyline { points, lengths }
+    }
+
+    pub fn points(&self) -> &[[f32; 2]] {
+        &self.points
+    }
+
+    pub fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or(0.0)
+    }
+
+    fn sample(&self, rng: &mut StdRng) -> Option<[f32; 2]> {
+        if self.lengths.is_empty() {
+            return self.points.first().copied();
+        }
+        let t = rng.gen::<f32>() * self.length();
+        let segment = self
+            .lengths
+            .partition_point(|&end| end < t)
+            .min(self.lengths.len() - 1);
+        let start = if segment == 0 {
+            0.0
+        } else {
+            self.lengths[segment - 1]
+        };
+        let along = (t - start) / (self.lengths[segment] - start).max(f32::EPSILON);
+        Some(lerp2(self.points[segment], self.points[segment + 1], along))
+    }
+}
+
+/// The pixels of an image that particles may spawn on: those with alpha, or
+/// brightness for images without alpha, of at least one half.
+#[derive(Debug, Clone)]
+pub struct ImageMask {
+    width: u32,
+    height: u32,
+    pixels: Vec<(u32, u32)>,
+}
+
+impl ImageMask {
+    /// Builds a mask from one byte per pixel, top row first.
+    pub fn from_coverage(width: u32, height: u32, coverage: &[u8]) -> ImageMask {
+        assert_eq!(
+            coverage.len(),
+            (width * height) as usize,
+            "mask size mismatch"
+        );
+        let pixels = coverage
+            .iter()
+            .enumerate()
+            .filter(|&(_, &value)| value >= 128)
+            .map(|(i, _)| (i as u32 % width, i as u32 / width))
+            .collect();
+        ImageMask {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<ImageMask, TextureError> {
+        let path = path.as_ref();
+        let image = match load(path) {
+            LoadResult::ImageU8(image) => image,
+            LoadResult::ImageF32(_) => {
+                return Err(TextureError::Load {
+                    path: path.to_path_buf(),
+                    message: "expected an 8-bit image".to_string(),
+                })
+            }
+            LoadResult::Error(message) => {
+                return Err(TextureError::Load {
+                    path: path.to_path_buf(),
+                    message,
+                })
+            }
+        };
+
+        let channels = image.depth;
+        let coverage: Vec<u8> = match channels {
+            1 | 3 => image
+                .data
+                .chunks_exact(channels)
+                .map(|pixel| (pixel.iter().map(|&c| c as u32).sum::<u32>() / channels as u32) as u8)
+                .collect(),
+            2 | 4 => image
+                .data
+                .chunks_exact(channels)
+                .map(|pixel| pixel[channels - 1])
+                .collect(),
+            _ => {
+                return Err(TextureError::UnsupportedChannels {
+                    path: path.to_path_buf(),
+                    channels,
+                })
+            }
+        };
+        Ok(ImageMask::from_coverage(
+            image.width as u32,
+            image.height as u32,
+            &coverage,
+        ))
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Number of pixels particles can spawn on.
+    pub fn len(&self) -> usize {
+        self.pixels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pixels.is_empty()
+    }
+
+    // A point in the mask from (0, 0) to (1, 1), top row at y = 0.
+    fn sample(&self, rng: &mut StdRng) -> Option<[f32; 2]> {
+        if self.pixels.is_empty() {
+            return None;
+        }
+        let (x, y) = self.pixels[rng.gen_range(0..self.pixels.len())];
+        Some([
+            (x as f32 + rng.gen::<f32>()) / self.width as f32,
+            (y as f32 + rng.gen::<f32>()) / self.height as f32,
+        ])
+    }
+}
+
+impl Shape {
+    /// A random point on the shape, or `None` for an empty polyline or mask.
+    pub fn sample(&self, rng: &mut StdRng) -> Option<[f32; 2]> {
+        match self {
+            Shape::Point(point) => Some(*point),
+            Shape::Line(a, b) => Some(lerp2(*a, *b, rng.gen())),
+            Shape::Circle { center, radius } => Some(ring(rng, *center, 0.0, *radius)),
+            Shape::Rect { min, max } => Some([
+                min[0] + (max[0] - min[0]) * rng.gen::<f32>(),
+                min[1] + (max[1] - min[1]) * rng.gen::<f32>(),
+            ]),
+            Shape::Ring {
+                center,
+                inner,
+                outer,
+            } => Some(ring(rng, *center, *inner, *outer)),
+            Shape::Polyline(polyline) => polyline.sample(rng),
+            Shape::Mask { mask, min, max } => {
+                let [u, v] = mask.sample(rng)?;
+                Some([
+                    min[0] + (max[0] - min[0]) * u,
+                    min[1] + (max[1] - min[1]) * v,
+                ])
+            }
+        }
+    }
+
+    /// Stretches the shape by `scale` about the origin. Circles and rings
+    /// stay round, with radii scaled by the mean of the two factors.
+    pub fn scale(&mut self, scale: [f32; 2]) {
+        let point = |p: &mut [f32; 2]| *p = [p[0] * scale[0], p[1] * scale[1]];
+        let radius = (scale[0] * scale[1]).abs().sqrt();
+        match self {
+            Shape::Point(p) => point(p),
+            Shape::Line(a, b) => {
+                point(a);
+                point(b);
+            }
+            Shape::Circle { center, radius: r } => {
+                point(center);
+                *r *= radius;
+            }
+            Shape::Rect { min, max } | Shape::Mask { min, max, .. } => {
+                point(min);
+                point(max);
+            }
+            Shape::Ring {
+                center,
+                inner,
+                outer,
+            } => {
+                point(center);
+                *inner *= radius;
+                *outer *= radius;
+            }
+            Shape::Polyline(polyline) => {
+                let mut points = polyline.points.clone();
+                points.iter_mut().for_each(point);
+                *polyline = Polyline::new(points);
+            }
+        }
+    }
+}
+
+// Uniform over the area, hence interpolating the squared radius.
+fn ring(rng: &mut StdRng, center: [f32; 2], inner: f32, outer: f32) -> [f32; 2] {
+    let angle = rng.gen::<f32>() * 2.0 * PI;
+    let r2 = inner * inner + (outer * outer - inner * inner) * rng.gen::<f32>();
+    let r = r2.sqrt();
+    [center[0] + r * angle.cos(), center[1] + r * angle.sin()]
+}
+
+fn lerp2(a: [f32; 2], b: [f32; 2], t: f32) -> [f32; 2] {
+    [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    ((b[0] - a[0]).powi(2) + (b[1] - a[1]).powi(2)).sqrt()
+}
+
+fn sample(rng: &mut StdRng, range: &RangeInclusive<f32>) -> f32 {
+    if range.start() == range.end() {
+        // Also keeps an infinite lifetime from turning into NaN.
+        return *range.start();
+    }
+    range.start() + (range.end() - range.start()) * rng.gen::<f32>()
+}
+
+/// When a `ShapeEmitter` spawns.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Emission {
+    /// Particles per second, spread evenly over the updates.
+    Rate(f32),
+    /// `count` particles on the first update, then again every `interval`
+    /// seconds if there is one.
+    Burst { count: usize, interval: Option<f32> },
+}
+
+/// An emitter configured from data: a shape to spawn on, how often to spawn,
+/// and ranges the new particles' speed, direction, size and lifetime are
+/// picked from. Spawning stops quietly while the pool is full.
+#[derive(Debug, Clone)]
+pub struct ShapeEmitter {
+    shape: Shape,
+    emission: Emission,
+    direction: f32,
+    spread: f32,
+    speed: RangeInclusive<f32>,
+    size: RangeInclusive<f32>,
+    lifetime: RangeInclusive<f32>,
+    pending: f32,
+    until_burst: Option<f32>,
+}
+
+impl ShapeEmitter {
+    /// Emits 10 particles per second, in every direction at speed 0, of size
+    /// 1, living forever.
+    pub fn new(shape: Shape) -> ShapeEmitter {
+        ShapeEmitter {
+            shape,
+            emission: Emission::Rate(10.0),
+            direction: 0.0,
+            spread: PI,
+            speed: 0.0..=0.0,
+            size: 1.0..=1.0,
+            lifetime: f32::INFINITY..=f32::INFINITY,
+            pending: 0.0,
+            until_burst: Some(0.0),
+        }
+    }
+
+    pub fn emission(mut self, emission: Emission) -> Self {
+        self.emission = emission;
+        self
+    }
+
+    pub fn rate(self, per_second: f32) -> Self {
+        self.emission(Emission::Rate(per_second))
+    }
+
+    /// A single burst of `count` particles.
+    pub fn burst(self, count: usize) -> Self {
+        self.emission(Emission::Burst {
+            count,
+            interval: None,
+        })
+    }
+
+    /// Particles leave within `spread` radians either side of `direction`,
+    /// an angle in the xy plane counterclockwise from +x. A spread of π is
+    /// every direction.
+    pub fn cone(mut self, direction: f32, spread: f32) -> Self {
+        self.direction = direction;
+        self.spread = spread.abs();
+        self
+    }
+
+    pub fn speed(mut self, speed: RangeInclusive<f32>) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn size(mut self, size: RangeInclusive<f32>) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Seconds each particle lives.
+    pub fn lifetime(mut self, lifetime: RangeInclusive<f32>) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    pub fn shape(&self) -> &Shape {
+        &self.shape
+    }
+
+    pub fn set_shape(&mut self, shape: Shape) {
+        self.shape = shape;
+    }
+
+    fn due(&mut self, dt: f32) -> usize {
+        match self.emission {
+            Emission::Rate(rate) => {
+                self.pending += rate * dt;
+                let count = self.pending.floor();
+                self.pending -= count;
+                count as usize
+            }
+            Emission::Burst { count, interval } => {
+                let Some(until_burst) = &mut self.until_burst else {
+                    return 0;
+                };
+                let mut due = 0;
+                if *until_burst <= 0.0 {
+                    due = count;
+                    match interval {
+                        Some(interval) => *until_burst += interval,
+                        None => {
+                            self.until_burst = None;
+                            return due;
+                        }
+                    }
+                }
+                *until_burst -= dt;
+                due
+            }
+        }
+    }
+
+    /// Makes one particle, or `None` if the shape is empty.
+    pub fn particle(&self, rng: &mut StdRng) -> Option<Particle> {
+        let [x, y] = self.shape.sample(rng)?;
+        let angle = self.direction + self.spread * (rng.gen::<f32>() * 2.0 - 1.0);
+        let speed = sample(rng, &self.speed);
+        Some(Particle {
+            position: [x, y, 0.0],
+            velocity: [speed * angle.cos(), speed * angle.sin(), 0.0],
+            size: sample(rng, &self.size),
+            lifetime: sample(rng, &self.lifetime),
+        })
+    }
+}
+
+impl Emitter for ShapeEmitter {
+    fn emit(&mut self, particles: &mut Particles, dt: f32, rng: &mut StdRng) {
+        for _ in 0..self.due(dt) {
+            if particles.is_full() {
+                break;
+            }
+            if let Some(particle) = self.particle(rng) {
+                particles.spawn(particle);
+            }
+        }
+    }
+
+    fn resize(&mut self, scale: [f32; 2]) {
+        self.shape.scale(scale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn rate_carries_fractions_over() {
+        let mut emitter = ShapeEmitter::new(Shape::Point([0.0, 0.0])).rate(30.0);
+        let total: usize = (0..60).map(|_| emitter.due(1.0 / 60.0)).sum();
+        assert!((29..=30).contains(&total));
+    }
+
+    #[test]
+    fn bursts_repeat_on_their_interval() {
+        let mut emitter = ShapeEmitter::new(Shape::Point([0.0, 0.0])).emission(Emission::Burst {
+            count: 5,
+            interval: Some(0.5),
+        });
+        let counts: Vec<usize> = (0..6).map(|_| emitter.due(0.25)).collect();
+        assert_eq!(counts, [5, 0, 5, 0, 5, 0]);
+
+        let mut once = ShapeEmitter::new(Shape::Point([0.0, 0.0])).burst(3);
+        assert_eq!(once.due(0.1), 3);
+        assert_eq!(once.due(10.0), 0);
+    }
+
+    #[test]
+    fn shapes_sample_inside_themselves() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let ring = Shape::Ring {
+            center: [1.0, 1.0],
+            inner: 2.0,
+            outer: 3.0,
+        };
+        let polyline = Shape::Polyline(Polyline::new(vec![[0.0, 0.0], [1.0, 0.0], [1.0, 4.0]]));
+        for _ in 0..100 {
+            let [x, y] = ring.sample(&mut rng).unwrap();
+            let r = distance([x, y], [1.0, 1.0]);
+            assert!((2.0 - 1e-4..=3.0 + 1e-4).contains(&r));
+
+            let [x, y] = polyline.sample(&mut rng).unwrap();
+            assert!(y.abs() < 1e-5 || (x - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn masks_only_spawn_on_covered_pixels() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mask = ImageMask::from_coverage(2, 2, &[0, 255, 0, 0]);
+        let shape = Shape::Mask {
+            mask,
+            min: [0.0, 0.0],
+            max: [20.0, 20.0],
+        };
+        for _ in 0..100 {
+            let [x, y] = shape.sample(&mut rng).unwrap();
+            assert!((10.0..=20.0).contains(&x) && (0.0..=10.0).contains(&y));
+        }
+
+        let empty = ImageMask::from_coverage(1, 1, &[0]);
+        assert!(empty.sample(&mut rng).is_none());
+    }
+}
@@ -0,0 +1,507 @@
+use super::noise::noise3;
+use super::{Force, Particles};
+use rand::rngs::StdRng;
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn length(a: [f32; 3]) -> f32 {
+    dot(a, a).sqrt()
+}
+
+fn accelerate(velocity: &mut [f32; 3], direction: [f32; 3], amount: f32) {
+    for (v, d) in velocity.iter_mut().zip(direction) {
+        *v += d * amount;
+    }
+}
+
+/// Constant acceleration, in units per second squared.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gravity(pub [f32; 3]);
+
+impl Force for Gravity {
+    fn apply(&mut self, particles: &mut Particles, dt: f32, _rng: &mut StdRng) {
+        for velocity in particles.attributes_mut().velocity {
+            accelerate(velocity, self.0, dt);
+        }
+    }
+}
+
+/// Slows particles down: the linear term like moving through syrup, the
+/// quadratic one like air at speed. Never reverses a particle, however long
+/// the step.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Drag {
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl Drag {
+    pub fn linear(linear: f32) -> Drag {
+        Drag {
+            linear,
+            quadratic: 0.0,
+        }
+    }
+
+    pub fn quadratic(quadratic: f32) -> Drag {
+        Drag {
+            linear: 0.0,
+            quadratic,
+        }
+    }
+}
+
+impl Force for Drag {
+    fn apply(&mut self, particles: &mut Particles, dt: f32, _rng: &mut StdRng) {
+        for velocity in particles.attributes_mut().velocity {
+            let speed = length(*velocity);
+            let keep = (1.0 - (self.linear + self.quadratic * speed) * dt).max(0.0);
+            *velocity = velocity.map(|v| v * keep);
+        }
+    }
+}
+
+/// How a force weakens with distance from its center. Beyond the force's
+/// radius it has no effect at all.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Falloff {
+    /// Full strength everywhere in range.
+    Constant,
+    /// Full strength at the center down to nothing at the radius.
+    Linear,
+    /// Strength divided by the squared distance, like gravity. Distances
+    /// below the softening length count as the softening length, so
+    /// particles passing through the center aren't flung off.
+    InverseSquare { softening: f32 },
+}
+
+impl Falloff {
+    fn weight(&self, distance: f32, radius: f32) -> f32 {
+        if distance > radius {
+            return 0.0;
+        }
+        match *self {
+            Falloff::Constant => 1.0,
+            Falloff::Linear if radius.is_finite() => 1.0 - distance / radius,
+            Falloff::Linear => 1.0,
+            Falloff::InverseSquare { softening } => {
+                let d = distance.max(softening).max(f32::EPSILON);
+                1.0 / (d * d)
+            }
+        }
+    }
+}
+
+/// Pulls particles towards a point, or pushes them away with a negative
+/// strength.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Attractor {
+    pub position: [f32; 3],
+    /// Acceleration at full weight; negative repels.
+    pub strength: f32,
+    pub radius: f32,
+    pub falloff: Falloff,
+}
+
+impl Attractor {
+    /// Pulls at constant strength over any distance.
+    pub fn new(position: [f32; 3], strength: f32) -> Attractor {
+        Attractor {
+            position,
+            strength,
+            radius: f32::INFINITY,
+            falloff: Falloff::Constant,
+        }
+    }
+
+    pub fn repulsor(position: [f32; 3], strength: f32) -> Attractor {
+        Attractor::new(position, -strength)
+    }
+
+    pub fn radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    pub fn falloff(mut self, falloff: Falloff) -> Self {
+        self.falloff = falloff;
+        self
+    }
+}
+
+impl Force for Attractor {
+    fn apply(&mut self, particles: &mut Particles, dt: f32, _rng: &mut StdRng) {
+        let attributes = particles.attributes_mut();
+        for (position, velocity) in attributes.position.iter().zip(attributes.velocity) {
+            let offset = sub(self.position, *position);
+            let distance = length(offset);
+            if distance <= f32::EPSILON {
+                continue;
+            }
+            let weight = self.falloff.weight(distance, self.radius);
+            accelerate(velocity, offset, self.strength * weight * dt / distance);
+        }
+    }
+
+    fn resize(&mut self, scale: [f32; 2]) {
+        self.position[0] *= scale[0];
+        self.position[1] *= scale[1];
+    }
+}
+
+/// Swirls particles around an axis through `center`, counterclockwise
+/// looking down the axis for a positive strength.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vortex {
+    pub center: [f32; 3],
+    pub axis: [f32; 3],
+    pub strength: f32,
+    pub radius: f32,
+    pub falloff: Falloff,
+}
+
+impl Vortex {
+    /// Swirls in the xy plane at constant strength over any distance.
+    pub fn new(center: [f32; 3], strength: f32) -> Vortex {
+        Vortex {
+            center,
+            axis: [0.0, 0.0, 1.0],
+            strength,
+            radius: f32::INFINITY,
+            falloff: Falloff::Constant,
+        }
+    }
+
+    pub fn axis(mut self, axis: [f32; 3]) -> Self {
+        self.axis = axis;
+        self
+    }
+
+    pub fn radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    pub fn falloff(mut self, falloff: Falloff) -> Self {
+        self.falloff = falloff;
+        self
+    }
+}
+
+impl Force for Vortex {
+    fn apply(&mut self, particles: &mut Particles, dt: f32, _rng: &mut StdRng) {
+        let axis_length = length(self.axis);
+        if axis_length <= f32::EPSILON {
+            return;
+        }
+        let axis = self.axis.map(|a| a / axis_length);
+
+        let attributes = particles.attributes_mut();
+        for (position, velocity) in attributes.position.iter().zip(attributes.velocity) {
+            let tangent = cross(axis, sub(*position, self.center));
+            // The tangent's length is the distance from the axis.
+            let distance = length(tangent);
+            if distance <= f32::EPSILON {
+                continue;
+            }
+            let weight = self.falloff.weight(distance, self.radius);
+            accelerate(velocity, tangent, self.strength * weight * dt / distance);
+        }
+    }
+
+    fn resize(&mut self, scale: [f32; 2]) {
+        self.center[0] *= scale[0];
+        self.center[1] *= scale[1];
+    }
+}
+
+/// Drags particles along with moving air: velocities approach the wind's
+/// at a rate set by `coupling`, per second.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Wind {
+    pub velocity: [f32; 3],
+    pub coupling: f32,
+}
+
+impl Wind {
+    pub fn new(velocity: [f32; 3], coupling: f32) -> Wind {
+        Wind { velocity, coupling }
+    }
+}
+
+impl Force for Wind {
+    fn apply(&mut self, particles: &mut Particles, dt: f32, _rng: &mut StdRng) {
+        let amount = (self.coupling * dt).min(1.0);
+        for velocity in particles.attributes_mut().velocity {
+            let relative = sub(self.velocity, *velocity);
+            accelerate(velocity, relative, amount);
+        }
+    }
+}
+
+/// Parameters shared by the noise-driven forces.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseField {
+    /// Acceleration at the noise's peaks.
+    pub strength: f32,
+    /// Size of the swirls, in the particles' units.
+    pub scale: f32,
+    /// How fast the field changes, in scales per second.
+    pub speed: f32,
+    pub seed: u32,
+}
+
+impl NoiseField {
+    pub fn new(strength: f32, scale: f32) -> NoiseField {
+        NoiseField {
+            strength,
+            scale,
+            speed: 0.5,
+            seed: 0,
+        }
+    }
+
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    // Noise space coordinates, scrolled through z over time so planar
+    // systems see the field change too.
+    fn point(&self, position: [f32; 3], time: f32) -> [f32; 3] {
+        let scale = self.scale.max(f32::EPSILON);
+        [
+            position[0] / scale,
+            position[1] / scale,
+            position[2] / scale + time * self.speed,
+        ]
+    }
+}
+
+/// Smoothly varying random pushes. Particles bunch up and spread out as
+/// they move through it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Turbulence {
+    pub field: NoiseField,
+    time: f32,
+}
+
+impl Turbulence {
+    pub fn new(field: NoiseField) -> Turbulence {
+        Turbulence { field, time: 0.0 }
+    }
+}
+
+impl Force for Turbulence {
+    fn apply(&mut self, particles: &mut Particles, dt: f32, _rng: &mut StdRng) {
+        self.time += dt;
+        let seed = self.field.seed;
+
+        let attributes = particles.attributes_mut();
+        for (position, velocity) in attributes.position.iter().zip(attributes.velocity) {
+            let p = self.field.point(*position, self.time);
+            let push = [
+                noise3(p, seed),
+                noise3(p, seed.wrapping_add(1)),
+                noise3(p, seed.wrapping_add(2)),
+            ];
+            accelerate(velocity, push, self.field.strength * dt);
+        }
+    }
+}
+
+// Step for the finite differences in `CurlNoise`, in noise space.
+const CURL_EPSILON: f32 = 1e-3;
+
+/// Swirling flow without sources or sinks, so particles stay evenly spread
+/// as it stirs them: the curl of a noise vector field. Planar flow only
+/// moves particles in the xy plane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurlNoise {
+    pub field: NoiseField,
+    pub planar: bool,
+    time: f32,
+}
+
+impl CurlNoise {
+    pub fn new(field: NoiseField) -> CurlNoise {
+        CurlNoise {
+            field,
+            planar: false,
+            time: 0.0,
+        }
+    }
+
+    pub fn planar(mut self, planar: bool) -> Self {
+        self.planar = planar;
+        self
+    }
+
+    fn curl(&self, p: [f32; 3]) -> [f32; 3] {
+        let seed = self.field.seed;
+        // d(potential[component]) / d(axis), by central differences.
+        let derivative = |component: u32, axis: usize| {
+            let (mut ahead, mut behind) = (p, p);
+            ahead[axis] += CURL_EPSILON;
+            behind[axis] -= CURL_EPSILON;
+            let seed = seed.wrapping_add(component);
+            (noise3(ahead, seed) - noise3(behind, seed)) / (2.0 * CURL_EPSILON)
+        };
+
+        if self.planar {
+            return [derivative(2, 1), -derivative(2, 0), 0.0];
+        }
+        [
+            derivative(2, 1) - derivative(1, 2),
+            derivative(0, 2) - derivative(2, 0),
+            derivative(1, 0) - derivative(0, 1),
+        ]
+    }
+}
+
+impl Force for CurlNoise {
+    fn apply(&mut self, particles: &mut Particles, dt: f32, _rng: &mut StdRng) {
+        self.time += dt;
+
+        let attributes = particles.attributes_mut();
+        for (position, velocity) in attributes.position.iter().zip(attributes.velocity) {
+            let curl = self.curl(self.field.point(*position, self.time));
+            accelerate(velocity, curl, self.field.strength * dt);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Particle;
+    use super::*;
+    use rand::SeedableRng;
+
+    fn apply(force: &mut impl Force, position: [f32; 3], velocity: [f32; 3], dt: f32) -> [f32; 3] {
+        let mut particles = Particles::new(1);
+        particles.spawn(Particle {
+            position,
+            velocity,
+            ..Particle::default()
+        });
+        force.apply(&mut particles, dt, &mut StdRng::seed_from_u64(0));
+        particles.velocity()[0]
+    }
+
+    fn close(a: [f32; 3], b: [f32; 3]) -> bool {
+        length(sub(a, b)) < 1e-4
+    }
+
+    #[test]
+    fn gravity_scales_with_dt() {
+        let mut gravity = Gravity([0.0, -10.0, 0.0]);
+        assert!(close(
+            apply(&mut gravity, [0.0; 3], [1.0, 0.0, 0.0], 0.5),
+            [1.0, -5.0, 0.0]
+        ));
+    }
+
+    #[test]
+    fn drag_never_reverses() {
+        let mut drag = Drag::linear(1.0);
+        assert!(close(
+            apply(&mut drag, [0.0; 3], [4.0, 0.0, 0.0], 0.25),
+            [3.0, 0.0, 0.0]
+        ));
+        assert!(close(
+            apply(&mut drag, [0.0; 3], [4.0, 0.0, 0.0], 10.0),
+            [0.0; 3]
+        ));
+
+        let mut quadratic = Drag::quadratic(0.1);
+        assert!(close(
+            apply(&mut quadratic, [0.0; 3], [0.0, 10.0, 0.0], 0.5),
+            [0.0, 5.0, 0.0]
+        ));
+    }
+
+    #[test]
+    fn attractors_pull_and_repulsors_push_within_range() {
+        let mut attractor = Attractor::new([10.0, 0.0, 0.0], 2.0).radius(20.0);
+        assert!(close(
+            apply(&mut attractor, [0.0; 3], [0.0; 3], 1.0),
+            [2.0, 0.0, 0.0]
+        ));
+
+        let mut repulsor = Attractor::repulsor([10.0, 0.0, 0.0], 2.0)
+            .radius(20.0)
+            .falloff(Falloff::Linear);
+        assert!(close(
+            apply(&mut repulsor, [0.0; 3], [0.0; 3], 1.0),
+            [-1.0, 0.0, 0.0]
+        ));
+
+        let mut out_of_range = attractor.radius(5.0);
+        assert!(close(
+            apply(&mut out_of_range, [0.0; 3], [0.0; 3], 1.0),
+            [0.0; 3]
+        ));
+    }
+
+    #[test]
+    fn vortex_pushes_sideways() {
+        let mut vortex = Vortex::new([0.0; 3], 3.0);
+        assert!(close(
+            apply(&mut vortex, [2.0, 0.0, 0.0], [0.0; 3], 1.0),
+            [0.0, 3.0, 0.0]
+        ));
+    }
+
+    #[test]
+    fn wind_approaches_its_velocity() {
+        let mut wind = Wind::new([10.0, 0.0, 0.0], 0.5);
+        assert!(close(
+            apply(&mut wind, [0.0; 3], [0.0; 3], 1.0),
+            [5.0, 0.0, 0.0]
+        ));
+        assert!(close(
+            apply(&mut wind, [0.0; 3], [0.0; 3], 5.0),
+            [10.0, 0.0, 0.0]
+        ));
+    }
+
+    #[test]
+    fn curl_noise_has_no_divergence() {
+        let curl = CurlNoise::new(NoiseField::new(1.0, 1.0));
+        let h = 1e-2;
+        for p in [[0.3, 0.7, 0.1], [2.5, -1.2, 4.4], [-3.1, 0.05, 1.9]] {
+            let mut divergence = 0.0;
+            for axis in 0..3 {
+                let (mut ahead, mut behind) = (p, p);
+                ahead[axis] += h;
+                behind[axis] -= h;
+                divergence += (curl.curl(ahead)[axis] - curl.curl(behind)[axis]) / (2.0 * h);
+            }
+            assert!(
+                divergence.abs() < 0.05,
+                "divergence {} at {:?}",
+                divergence,
+                p
+            );
+        }
+    }
+}
@@ -0,0 +1,550 @@
+mod curve;
+mod emitter;
+mod force;
+mod noise;
+mod render;
+
+pub use curve::{Curve, Fade, Gradient, LifeCurves, ParseCurveError};
+pub use emitter::{Emission, ImageMask, Polyline, Shape, ShapeEmitter};
+pub use force::{
+    Attractor, CurlNoise, Drag, Falloff, Gravity, NoiseField, Turbulence, Vortex, Wind,
+};
+pub use render::{
+    CurveEvaluation, ParticleRenderer, Sprite, LIFE_CURVES_GLSL, LIFE_CURVES_INCLUDE,
+    LIFE_CURVES_RESOLUTION, LIFE_CURVES_UNIT,
+};
+
+use rand::rngs::StdRng;
+
+/// The starting state of a particle handed to `Particles::spawn`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Particle {
+    pub position: [f32; 3],
+    /// Units per second.
+    pub velocity: [f32; 3],
+    pub size: f32,
+    /// Seconds until the particle dies; `f32::INFINITY` for never.
+    pub lifetime: f32,
+}
+
+impl Default for Particle {
+    fn default() -> Self {
+        Particle {
+            position: [0.0; 3],
+            velocity: [0.0; 3],
+            size: 1.0,
+            lifetime: f32::INFINITY,
+        }
+    }
+}
+
+/// Live particles stored as one array per attribute, packed at the front so
+/// spawning appends and killing swaps the last particle into the hole. Both
+/// are O(1), and the order of particles is not stable across a kill.
+pub struct Particles {
+    capacity: usize,
+    position: Vec<[f32; 3]>,
+    previous: Vec<[f32; 3]>,
+    velocity: Vec<[f32; 3]>,
+    size: Vec<f32>,
+    age: Vec<f32>,
+    lifetime: Vec<f32>,
+}
+
+/// Mutable views of every attribute at once, for code that reads one while
+/// writing another. All slices have `Particles::len` elements.
+pub struct ParticlesMut<'a> {
+    pub position: &'a mut [[f32; 3]],
+    pub velocity: &'a mut [[f32; 3]],
+    pub size: &'a mut [f32],
+    pub age: &'a mut [f32],
+    pub lifetime: &'a mut [f32],
+}
+
+impl Particles {
+    pub fn new(capacity: usize) -> Particles {
+        Particles {
+            capacity,
+            position: Vec::with_capacity(capacity),
+            previous: Vec::with_capacity(capacity),
+            velocity: Vec::with_capacity(capacity),
+            size: Vec::with_capacity(capacity),
+            age: Vec::with_capacity(capacity),
+            lifetime: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.position.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.position.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity
+    }
+
+    /// Adds a particle and returns its index, or `None` when the pool is
+    /// full.
+    pub fn spawn(&mut self, particle: Particle) -> Option<usize> {
+        if self.is_full() {
+            return None;
+        }
+        self.position.push(particle.position);
+        self.previous.push(particle.position);
+        self.velocity.push(particle.velocity);
+        self.size.push(particle.size);
+        self.age.push(0.0);
+        self.lifetime.push(particle.lifetime);
+        Some(self.len() - 1)
+    }
+
+    /// Removes the particle at `index`, moving the last particle into its
+    /// place. Loops that kill as they go should run back to front.
+    pub fn kill(&mut self, index: usize) {
+        self.position.swap_remove(index);
+        self.previous.swap_remove(index);
+        self.velocity.swap_remove(index);
+        self.size.swap_remove(index);
+        self.age.swap_remove(index);
+        self.lifetime.swap_remove(index);
+    }
+
+    pub fn clear(&mut self) {
+        self.position.clear();
+        self.previous.clear();
+        self.velocity.clear();
+        self.size.clear();
+        self.age.clear();
+        self.lifetime.clear();
+    }
+
+    pub fn position(&self) -> &[[f32; 3]] {
+        &self.position
+    }
+
+    /// Where each particle was before the last `ParticleSystem::update`
+    /// moved it, or where it spawned if that update spawned it.
+    pub fn previous_position(&self) -> &[[f32; 3]] {
+        &self.previous
+    }
+
+    /// Positions `alpha` of the way from `previous_position` to `position`,
+    /// for drawing between updates.
+    pub fn interpolated_position(&self, alpha: f32) -> impl Iterator<Item = [f32; 3]> + '_ {
+        self.previous
+            .iter()
+            .zip(&self.position)
+            .map(move |(previous, current)| {
+                [0, 1, 2].map(|axis| previous[axis] + (current[axis] - previous[axis]) * alpha)
+            })
+    }
+
+    pub fn velocity(&self) -> &[[f32; 3]] {
+        &self.velocity
+    }
+
+    pub fn size(&self) -> &[f32] {
+        &self.size
+    }
+
+    /// Seconds since each particle spawned.
+    pub fn age(&self) -> &[f32] {
+        &self.age
+    }
+
+    pub fn lifetime(&self) -> &[f32] {
+        &self.lifetime
+    }
+
+    pub fn attributes_mut(&mut self) -> ParticlesMut<'_> {
+        ParticlesMut {
+            position: &mut self.position,
+            velocity: &mut self.velocity,
+            size: &mut self.size,
+            age: &mut self.age,
+            lifetime: &mut self.lifetime,
+        }
+    }
+}
+
+/// Spawns new particles each update.
+pub trait Emitter {
+    fn emit(&mut self, particles: &mut Particles, dt: f32, rng: &mut StdRng);
+
+    /// Stretches where the emitter spawns by `scale` about the origin when
+    /// the world is resized.
+    fn resize(&mut self, _scale: [f32; 2]) {}
+}
+
+/// Changes particles each update before they move, usually by adding to
+/// their velocity.
+pub trait Force {
+    fn apply(&mut self, particles: &mut Particles, dt: f32, rng: &mut StdRng);
+
+    /// Moves anything the force has a position for by `scale` about the
+    /// origin when the world is rescaled.
+    fn resize(&mut self, _scale: [f32; 2]) {}
+}
+
+impl<F: FnMut(&mut Particles, f32, &mut StdRng)> Emitter for F {
+    fn emit(&mut self, particles: &mut Particles, dt: f32, rng: &mut StdRng) {
+        self(particles, dt, rng)
+    }
+}
+
+impl<F: FnMut(&mut Particles, f32, &mut StdRng)> Force for F {
+    fn apply(&mut self, particles: &mut Particles, dt: f32, rng: &mut StdRng) {
+        self(particles, dt, rng)
+    }
+}
+
+/// What happens to a particle that leaves `Bounds`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Edge {
+    /// Reappears on the opposite side.
+    Wrap,
+    /// Is put back on the edge and bounces off it, keeping this fraction of
+    /// its speed.
+    Bounce(f32),
+    /// Dies.
+    Kill,
+}
+
+/// How `ParticleSystem::resize_world` treats a world whose size changed,
+/// usually because the window did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorldResize {
+    /// Stretches everything with the world: particles, their velocities,
+    /// bounds, emitters and forces. The picture looks the same at any size.
+    Rescale,
+    /// Grows or shrinks the bounds and emitter shapes, leaving particles and
+    /// forces where they are. More of the world shows in a bigger window.
+    Extend,
+}
+
+/// A box particles are kept in. Axes with an infinite extent are unbounded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+    pub edge: Edge,
+}
+
+impl Bounds {
+    pub fn new(min: [f32; 3], max: [f32; 3], edge: Edge) -> Bounds {
+        Bounds { min, max, edge }
+    }
+
+    /// A box around x and y only.
+    pub fn rect(min: [f32; 2], max: [f32; 2], edge: Edge) -> Bounds {
+        Bounds::new(
+            [min[0], min[1], f32::NEG_INFINITY],
+            [max[0], max[1], f32::INFINITY],
+            edge,
+        )
+    }
+
+    fn resize(&mut self, scale: [f32; 2]) {
+        for (axis, scale) in scale.into_iter().enumerate() {
+            self.min[axis] *= scale;
+            self.max[axis] *= scale;
+        }
+    }
+
+    fn apply(&self, particles: &mut Particles) {
+        for i in (0..particles.len()).rev() {
+            let position = &mut particles.position[i];
+            let velocity = &mut particles.velocity[i];
+            let mut outside = false;
+            let mut wrapped = false;
+
+            for axis in 0..3 {
+                let (min, max) = (self.min[axis], self.max[axis]);
+                if !(min.is_finite() && max.is_finite()) {
+                    continue;
+                }
+                let p = &mut position[axis];
+                if *p >= min && *p <= max {
+                    continue;
+                }
+                outside = true;
+
+                match self.edge {
+                    Edge::Wrap => {
+                        *p = min + (*p - min).rem_euclid(max - min);
+                        wrapped = true;
+                    }
+                    Edge::Bounce(restitution) => {
+                        *p = p.clamp(min, max);
+                        velocity[axis] *= -restitution;
+                    }
+                    Edge::Kill => {}
+                }
+            }
+
+            // Jump rather than sweep across the world between updates.
+            if wrapped {
+                particles.previous[i] = *position;
+            }
+            if outside && self.edge == Edge::Kill {
+                particles.kill(i);
+            }
+        }
+    }
+}
+
+/// A particle pool with the emitters and forces that drive it. Each update
+/// ages and retires particles, runs the emitters, applies the forces, moves
+/// every particle by its velocity and finally keeps them in bounds. The
+/// positions before the update are kept for interpolating between updates.
+pub struct ParticleSystem {
+    particles: Particles,
+    emitters: Vec<Box<dyn Emitter>>,
+    forces: Vec<Box<dyn Force>>,
+    bounds: Option<Bounds>,
+    curves: LifeCurves,
+}
+
+impl ParticleSystem {
+    pub fn new(capacity: usize) -> ParticleSystem {
+        ParticleSystem {
+            particles: Particles::new(capacity),
+            emitters: Vec::new(),
+            forces: Vec::new(),
+            bounds: None,
+            curves: LifeCurves::default(),
+        }
+    }
+
+    pub fn emitter(mut self, emitter: impl Emitter + 'static) -> Self {
+        self.emitters.push(Box::new(emitter));
+        self
+    }
+
+    pub fn force(mut self, force: impl Force + 'static) -> Self {
+        self.forces.push(Box::new(force));
+        self
+    }
+
+    pub fn bounds(mut self, bounds: Bounds) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn set_bounds(&mut self, bounds: Option<Bounds>) {
+        self.bounds = bounds;
+    }
+
+    /// How particles look over their lives when rendered.
+    pub fn curves(mut self, curves: LifeCurves) -> Self {
+        self.curves = curves;
+        self
+    }
+
+    pub fn set_curves(&mut self, curves: LifeCurves) {
+        self.curves = curves;
+    }
+
+    pub fn life_curves(&self) -> &LifeCurves {
+        &self.curves
+    }
+
+    pub fn particles(&self) -> &Particles {
+        &self.particles
+    }
+
+    pub fn particles_mut(&mut self) -> &mut Particles {
+        &mut self.particles
+    }
+
+    /// Adapts the system to a world whose x and y extents were multiplied by
+    /// `scale`, such as a pixel-space world whose window went from `old` to
+    /// `new` pixels wide with a scale of `new / old`. Scaling is about the
+    /// origin, so worlds should start at 0 or be centred on it.
+    pub fn resize_world(&mut self, scale: [f32; 2], mode: WorldResize) {
+        if let Some(bounds) = &mut self.bounds {
+            bounds.resize(scale);
+        }
+        for emitter in &mut self.emitters {
+            emitter.resize(scale);
+        }
+        if mode == WorldResize::Extend {
+            return;
+        }
+
+        for force in &mut self.forces {
+            force.resize(scale);
+        }
+        let particles = &mut self.particles;
+        let moving = particles.position.iter_mut().zip(&mut particles.velocity);
+        for ((position, velocity), previous) in moving.zip(&mut particles.previous) {
+            for axis in 0..2 {
+                position[axis] *= scale[axis];
+                velocity[axis] *= scale[axis];
+                previous[axis] *= scale[axis];
+            }
+        }
+    }
+
+    pub fn update(&mut self, dt: f32, rng: &mut StdRng) {
+        let particles = &mut self.particles;
+        particles.previous.copy_from_slice(&particles.position);
+
+        for i in (0..particles.len()).rev() {
+            particles.age[i] += dt;
+            if particles.age[i] >= particles.lifetime[i] {
+                particles.kill(i);
+            }
+        }
+
+        for emitter in &mut self.emitters {
+            emitter.emit(particles, dt, rng);
+        }
+        for force in &mut self.forces {
+            force.apply(particles, dt, rng);
+        }
+
+        for (position, velocity) in particles.position.iter_mut().zip(&particles.velocity) {
+            for axis in 0..3 {
+                position[axis] += velocity[axis] * dt;
+            }
+        }
+
+        if let Some(bounds) = &self.bounds {
+            bounds.apply(particles);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn at(x: f32) -> Particle {
+        Particle {
+            position: [x, 0.0, 0.0],
+            ..Particle::default()
+        }
+    }
+
+    #[test]
+    fn spawn_stops_at_capacity() {
+        let mut particles = Particles::new(2);
+        assert_eq!(particles.spawn(at(0.0)), Some(0));
+        assert_eq!(particles.spawn(at(1.0)), Some(1));
+        assert_eq!(particles.spawn(at(2.0)), None);
+        assert!(particles.is_full());
+    }
+
+    #[test]
+    fn kill_moves_the_last_particle_into_the_hole() {
+        let mut particles = Particles::new(3);
+        for x in 0..3 {
+            particles.spawn(at(x as f32));
+        }
+        particles.kill(0);
+        assert_eq!(particles.len(), 2);
+        assert_eq!(particles.position()[0][0], 2.0);
+        assert_eq!(particles.position()[1][0], 1.0);
+        assert_eq!(particles.spawn(at(3.0)), Some(2));
+    }
+
+    #[test]
+    fn expired_particles_are_retired() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut system = ParticleSystem::new(4);
+        for lifetime in [0.5, 1.5, f32::INFINITY] {
+            system.particles_mut().spawn(Particle {
+                lifetime,
+                ..Particle::default()
+            });
+        }
+
+        system.update(1.0, &mut rng);
+        assert_eq!(system.particles().len(), 2);
+        system.update(1.0, &mut rng);
+        assert_eq!(system.particles().len(), 1);
+        assert_eq!(system.particles().lifetime()[0], f32::INFINITY);
+    }
+
+    #[test]
+    fn bounds_wrap_and_bounce() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut system =
+            ParticleSystem::new(1).bounds(Bounds::rect([0.0, 0.0], [10.0, 10.0], Edge::Wrap));
+        system.particles_mut().spawn(Particle {
+            position: [9.0, 5.0, 100.0],
+            velocity: [3.0, 0.0, 0.0],
+            ..Particle::default()
+        });
+        system.update(1.0, &mut rng);
+        assert!((system.particles().position()[0][0] - 2.0).abs() < 1e-5);
+        assert_eq!(system.particles().position()[0][2], 100.0);
+
+        system.set_bounds(Some(Bounds::rect(
+            [0.0, 0.0],
+            [10.0, 10.0],
+            Edge::Bounce(0.5),
+        )));
+        system.update(3.0, &mut rng);
+        assert_eq!(system.particles().position()[0][0], 10.0);
+        assert_eq!(system.particles().velocity()[0][0], -1.5);
+    }
+
+    #[test]
+    fn positions_interpolate_from_before_the_update() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut system =
+            ParticleSystem::new(2).bounds(Bounds::rect([0.0, 0.0], [10.0, 10.0], Edge::Wrap));
+        for (x, vx) in [(2.0, 4.0), (9.0, 2.0)] {
+            system.particles_mut().spawn(Particle {
+                position: [x, 5.0, 0.0],
+                velocity: [vx, 0.0, 0.0],
+                ..Particle::default()
+            });
+        }
+        assert_eq!(
+            system.particles().interpolated_position(0.5).next(),
+            Some([2.0, 5.0, 0.0])
+        );
+
+        system.update(1.0, &mut rng);
+        let halfway: Vec<_> = system.particles().interpolated_position(0.5).collect();
+        assert_eq!(halfway[0], [4.0, 5.0, 0.0]);
+        // The second particle wrapped, so it shows where it landed.
+        assert_eq!(halfway[1], system.particles().position()[1]);
+    }
+
+    #[test]
+    fn resize_world_rescales_or_extends() {
+        let spawn = |system: &mut ParticleSystem| {
+            system.particles_mut().spawn(Particle {
+                position: [4.0, 3.0, 1.0],
+                velocity: [2.0, 2.0, 2.0],
+                ..Particle::default()
+            });
+        };
+        let bounds = Bounds::rect([0.0, 0.0], [10.0, 10.0], Edge::Wrap);
+
+        let mut rescaled = ParticleSystem::new(1).bounds(bounds);
+        spawn(&mut rescaled);
+        rescaled.resize_world([2.0, 0.5], WorldResize::Rescale);
+        assert_eq!(rescaled.particles().position()[0], [8.0, 1.5, 1.0]);
+        assert_eq!(rescaled.particles().velocity()[0], [4.0, 1.0, 2.0]);
+        assert_eq!(rescaled.bounds.unwrap().max[..2], [20.0, 5.0]);
+
+        let mut extended = ParticleSystem::new(1).bounds(bounds);
+        spawn(&mut extended);
+        extended.resize_world([2.0, 0.5], WorldResize::Extend);
+        assert_eq!(extended.particles().position()[0], [4.0, 3.0, 1.0]);
+        assert_eq!(extended.bounds.unwrap().max[..2], [20.0, 5.0]);
+    }
+}
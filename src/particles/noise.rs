@@ -0,0 +1,84 @@
+// Seeded 3D gradient noise for the turbulence forces. Lattice gradients come
+// from an integer hash rather than a permutation table, so any seed works
+// without setup.
+
+fn hash(x: i32, y: i32, z: i32, seed: u32) -> u32 {
+    let mut h = seed
+        ^ (x as u32).wrapping_mul(0x8da6_b343)
+        ^ (y as u32).wrapping_mul(0xd816_3841)
+        ^ (z as u32).wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5bd1_e995);
+    h ^ (h >> 15)
+}
+
+// One of the 12 edge directions of a cube, as in improved Perlin noise.
+fn gradient(hash: u32, x: f32, y: f32, z: f32) -> f32 {
+    match hash % 12 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x + z,
+        5 => -x + z,
+        6 => x - z,
+        7 => -x - z,
+        8 => y + z,
+        9 => -y + z,
+        10 => y - z,
+        _ => -y - z,
+    }
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Smooth noise of roughly -1 to 1 with features about one unit apart.
+pub(crate) fn noise3(p: [f32; 3], seed: u32) -> f32 {
+    let cell = p.map(|c| c.floor());
+    let [x, y, z] = cell.map(|c| c as i32);
+    let [fx, fy, fz] = [p[0] - cell[0], p[1] - cell[1], p[2] - cell[2]];
+    let corner = |dx: i32, dy: i32, dz: i32| {
+        let h = hash(
+            x.wrapping_add(dx),
+            y.wrapping_add(dy),
+            z.wrapping_add(dz),
+            seed,
+        );
+        gradient(h, fx - dx as f32, fy - dy as f32, fz - dz as f32)
+    };
+
+    let (u, v, w) = (fade(fx), fade(fy), fade(fz));
+    lerp(
+        lerp(
+            lerp(corner(0, 0, 0), corner(1, 0, 0), u),
+            lerp(corner(0, 1, 0), corner(1, 1, 0), u),
+            v,
+        ),
+        lerp(
+            lerp(corner(0, 0, 1), corner(1, 0, 1), u),
+            lerp(corner(0, 1, 1), corner(1, 1, 1), u),
+            v,
+        ),
+        w,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn huge_coordinates_wrap_instead_of_overflowing() {
+        // Far enough out that the cell saturates at i32::MAX.
+        for p in [[1e20, 0.5, -1e20], [f32::MAX, f32::MIN, 2.5e9]] {
+            let n = noise3(p, 7);
+            assert!((-1.0..=1.0).contains(&n), "{:?} gave {}", p, n);
+        }
+    }
+}
@@ -0,0 +1,249 @@
+extern crate gl;
+use super::{LifeCurves, ParticleSystem};
+use crate::buffer::{Buffer, Usage};
+use crate::sampler::{Filter, SamplerOptions, Wrap};
+use crate::texture::{Texture2D, TextureFormat, TextureOptions};
+use crate::vertex_array::{VertexArray, VertexLayout};
+use gl::types::*;
+use std::mem;
+
+/// How each particle is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sprite {
+    /// One `GL_POINTS` vertex; the shader sets `gl_PointSize`.
+    Point,
+    /// A two-triangle strip with corners from -1 to 1.
+    Quad,
+}
+
+/// Where a system's `LifeCurves` are evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurveEvaluation {
+    /// Per particle while streaming; `a_size` and `a_color` arrive final.
+    Cpu,
+    /// In the vertex shader, from a lookup texture baked whenever the curves
+    /// change. `a_size` is the particle's own size, `a_color` is white, and
+    /// the shader includes `LIFE_CURVES_GLSL` to apply the curves.
+    Gpu,
+}
+
+/// Texture unit the baked curves are bound to for `CurveEvaluation::Gpu`.
+pub const LIFE_CURVES_UNIT: GLuint = 0;
+
+/// Ages sampled when baking curves into a texture.
+pub const LIFE_CURVES_RESOLUTION: usize = 256;
+
+/// The name to register `LIFE_CURVES_GLSL` under, with
+/// `ShaderFile::builtin` or `Preprocessor::builtin`.
+pub const LIFE_CURVES_INCLUDE: &str = "glrs/particles.glsl";
+
+/// Applies curves baked for `CurveEvaluation::Gpu` in the vertex shader,
+/// with `life_color(life)` and `life_size(life)`.
+pub static LIFE_CURVES_GLSL: &str =
+    "// Life curves baked by ParticleRenderer with CurveEvaluation::Gpu: color
+// in the first row, size in the red channel of the second.
+uniform sampler2D u_life_curves;
+
+vec4 life_curves_row(float life, float row) {
+    float n = float(textureSize(u_life_curves, 0).x);
+    float u = (clamp(life, 0.0, 1.0) * (n - 1.0) + 0.5) / n;
+    return texture(u_life_curves, vec2(u, row));
+}
+
+vec4 life_color(float life) {
+    return life_curves_row(life, 0.25);
+}
+
+float life_size(float life) {
+    return life_curves_row(life, 0.75).r;
+}
+";
+
+// Per-instance data, one per live particle. Location 0 is the quad corner;
+// the rest follow on in order.
+#[repr(C)]
+#[derive(Clone, Copy, VertexLayout)]
+struct Instance {
+    #[vertex(location = 1)]
+    position: [f32; 3],
+    size: f32,
+    color: [f32; 4],
+    life: f32,
+}
+
+const QUAD: [[f32; 2]; 4] = [[-1.0, -1.0], [1.0, -1.0], [-1.0, 1.0], [1.0, 1.0]];
+
+/// Draws a particle system with instancing. The bound program sees:
+///
+/// ```glsl
+/// layout(location=0) in vec2 a_corner;   // quad corner, (-1,-1) for points
+/// layout(location=1) in vec3 a_position;
+/// layout(location=2) in float a_size;
+/// layout(location=3) in vec4 a_color;
+/// layout(location=4) in float a_life;    // age over lifetime, 0 to 1
+/// ```
+pub struct ParticleRenderer {
+    vao: VertexArray,
+    _corners: Buffer,
+    instances: Buffer,
+    data: Vec<Instance>,
+    sprite: Sprite,
+    evaluation: CurveEvaluation,
+    // The curves the lookup texture was baked from.
+    baked: Option<(LifeCurves, Texture2D)>,
+}
+
+impl ParticleRenderer {
+    pub fn new(capacity: usize, sprite: Sprite) -> ParticleRenderer {
+        let vao = VertexArray::new();
+
+        let mut corners = Buffer::array();
+        corners.upload(&QUAD, Usage::Static);
+        vao.attrib_f32(&corners, 0, 2, 0, 0, 0);
+
+        let mut instances = Buffer::array();
+        instances.allocate(capacity * mem::size_of::<Instance>(), Usage::Stream);
+        vao.bind_layout::<Instance>(&instances, 1);
+
+        ParticleRenderer {
+            vao,
+            _corners: corners,
+            instances,
+            data: Vec::with_capacity(capacity),
+            sprite,
+            evaluation: CurveEvaluation::Cpu,
+            baked: None,
+        }
+    }
+
+    /// Defaults to `CurveEvaluation::Cpu`.
+    pub fn evaluation(mut self, evaluation: CurveEvaluation) -> Self {
+        self.evaluation = evaluation;
+        self
+    }
+
+    /// Copies the system's live particles into the instance buffer, placed
+    /// `alpha` of the way from their positions before the last update to
+    /// their current ones.
+    pub fn stream(&mut self, system: &ParticleSystem, alpha: f32) {
+        let particles = system.particles();
+        let curves = system.life_curves();
+        let lives = particles
+            .age()
+            .iter()
+            .zip(particles.lifetime())
+            .map(|(&age, &lifetime)| (age / lifetime).min(1.0));
+
+        self.data.clear();
+        let attributes = particles
+            .interpolated_position(alpha)
+            .zip(particles.size())
+            .zip(lives);
+        match self.evaluation {
+            CurveEvaluation::Cpu => {
+                self.data
+                    .extend(attributes.map(|((position, &size), life)| Instance {
+                        position,
+                        size: size * curves.size_at(life),
+                        color: curves.color_at(life),
+                        life,
+                    }))
+            }
+            CurveEvaluation::Gpu => {
+                self.data
+                    .extend(attributes.map(|((position, &size), life)| Instance {
+                        position,
+                        size,
+                        color: [1.0; 4],
+                        life,
+                    }));
+                self.bake(curves);
+            }
+        }
+        // The system may hold more than the renderer was made for.
+        let size = self.data.capacity() * mem::size_of::<Instance>();
+        if size > self.instances.size() {
+            self.instances.allocate(size, Usage::Stream);
+        }
+        self.instances.update(0, &self.data);
+    }
+
+    fn bake(&mut self, curves: &LifeCurves) {
+        if matches!(&self.baked, Some((baked, _)) if baked == curves) {
+            return;
+        }
+
+        let options = TextureOptions::new().mipmaps(false).sampler(
+            SamplerOptions::new()
+                .wrap(Wrap::ClampToEdge)
+                .filter(Filter::Linear),
+        );
+        let mut lookup = Texture2D::new();
+        lookup.upload(
+            LIFE_CURVES_RESOLUTION as i32,
+            2,
+            TextureFormat::Rgba32F,
+            &curves.bake(LIFE_CURVES_RESOLUTION),
+            &options,
+        );
+        self.baked = Some((curves.clone(), lookup));
+    }
+
+    /// Draws the particles from the last `stream` with the bound program.
+    pub fn draw(&self) {
+        let (mode, vertices) = match self.sprite {
+            Sprite::Point => (gl::POINTS, 1),
+            Sprite::Quad => (gl::TRIANGLE_STRIP, 4),
+        };
+        if let (CurveEvaluation::Gpu, Some((_, lookup))) = (self.evaluation, &self.baked) {
+            lookup.bind(LIFE_CURVES_UNIT);
+        }
+        self.vao.bind();
+        unsafe {
+            gl::DrawArraysInstanced(mode, 0, vertices, self.data.len() as GLsizei);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless;
+    use crate::particles::Particle;
+
+    #[test]
+    fn streaming_grows_past_the_renderer_capacity() {
+        let Some(_gl) = headless::test_context() else {
+            return;
+        };
+
+        let mut system = ParticleSystem::new(100);
+        for i in 0..100 {
+            system.particles_mut().spawn(Particle {
+                position: [i as f32, 0.0, 0.0],
+                ..Particle::default()
+            });
+        }
+        let mut renderer = ParticleRenderer::new(10, Sprite::Point);
+        renderer.stream(&system, 1.0);
+        assert_eq!(renderer.data.len(), 100);
+        assert!(renderer.instances.size() >= 100 * mem::size_of::<Instance>());
+
+        let mut last = Instance {
+            position: [0.0; 3],
+            size: 0.0,
+            color: [0.0; 4],
+            life: 0.0,
+        };
+        let offset = 99 * mem::size_of::<Instance>();
+        unsafe {
+            gl::GetBufferSubData(
+                gl::ARRAY_BUFFER,
+                offset as GLintptr,
+                mem::size_of::<Instance>() as GLsizeiptr,
+                &mut last as *mut Instance as *mut GLvoid,
+            );
+        }
+        assert_eq!(last.position, [99.0, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,561 @@
+extern crate gl;
+use crate::app::{self, CreateFn, Demo};
+use crate::framebuffer::Framebuffer;
+use crate::input::Input;
+use crate::preprocess::Preprocessor;
+use crate::program::Program;
+use crate::shader::{Shader, ShaderError, ShaderStage};
+use crate::vertex_array::VertexArray;
+use gl::types::*;
+use glutin::event::WindowEvent;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::error::Error;
+use std::str::FromStr;
+
+/// `glrs/transition.glsl`, included ahead of every transition.
+static TRANSITION_GLSL: &str = "// The gl-transitions interface, for playlist transitions.
+uniform sampler2D u_from;
+uniform sampler2D u_to;
+// 0 when only the outgoing demo shows, 1 when only the incoming one does.
+uniform float progress;
+// Width over height.
+uniform float ratio;
+
+in vec2 v_uv;
+out vec4 out_color;
+
+vec4 getFromColor(vec2 uv) {
+    return texture(u_from, uv);
+}
+
+vec4 getToColor(vec2 uv) {
+    return texture(u_to, uv);
+}
+";
+
+static VS_SRC: &str = "
+out vec2 v_uv;
+
+void main() {
+    // One triangle covering the screen, made from the vertex index alone.
+    vec2 corner = vec2(float((gl_VertexID << 1) & 2), float(gl_VertexID & 2));
+    v_uv = corner;
+    gl_Position = vec4(corner * 2.0 - 1.0, 0.0, 1.0);
+}
+";
+
+static CUT: &str = "
+vec4 transition(vec2 uv) {
+    return getToColor(uv);
+}
+";
+
+static CROSSFADE: &str = "
+vec4 transition(vec2 uv) {
+    return mix(getFromColor(uv), getToColor(uv), progress);
+}
+";
+
+static WIPE: &str = "
+uniform float smoothness; // = 0.1
+
+vec4 transition(vec2 uv) {
+    float edge = progress * (1.0 + smoothness);
+    float t = smoothstep(edge - smoothness, edge, uv.x);
+    return mix(getToColor(uv), getFromColor(uv), t);
+}
+";
+
+static DISSOLVE: &str = "
+uniform float cells; // = 48.0
+uniform float smoothness; // = 0.05
+
+float hash(vec2 p) {
+    return fract(sin(dot(p, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+// Value noise over square cells, whatever the aspect ratio.
+float noise(vec2 uv) {
+    vec2 p = uv * vec2(cells * ratio, cells);
+    vec2 i = floor(p);
+    vec2 f = smoothstep(0.0, 1.0, fract(p));
+    return mix(
+        mix(hash(i), hash(i + vec2(1.0, 0.0)), f.x),
+        mix(hash(i + vec2(0.0, 1.0)), hash(i + vec2(1.0, 1.0)), f.x),
+        f.y);
+}
+
+vec4 transition(vec2 uv) {
+    float edge = progress * (1.0 + smoothness);
+    float t = smoothstep(edge - smoothness, edge, noise(uv));
+    return mix(getToColor(uv), getFromColor(uv), t);
+}
+";
+
+/// How one demo gives way to the next.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transition {
+    /// Switches at once, whatever the transition time.
+    Cut,
+    Crossfade,
+    /// The next demo sweeps in from the left.
+    Wipe,
+    /// The next demo shows through in growing patches of noise.
+    Dissolve,
+    /// GLSL in the gl-transitions format (<https://gl-transitions.com>):
+    /// it defines `vec4 transition(vec2 uv)` using `getFromColor`,
+    /// `getToColor`, `progress` and `ratio`. Parameters declared as
+    /// `uniform float name; // = value` take their default value.
+    Custom(String),
+}
+
+/// Parses the name of a built-in transition.
+impl FromStr for Transition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cut" => Ok(Transition::Cut),
+            "crossfade" => Ok(Transition::Crossfade),
+            "wipe" => Ok(Transition::Wipe),
+            "dissolve" => Ok(Transition::Dissolve),
+            _ => Err(format!(
+                "unknown transition `{}`, expected cut, crossfade, wipe or dissolve",
+                s
+            )),
+        }
+    }
+}
+
+impl Transition {
+    fn source(&self) -> &str {
+        match self {
+            Transition::Cut => CUT,
+            Transition::Crossfade => CROSSFADE,
+            Transition::Wipe => WIPE,
+            Transition::Dissolve => DISSOLVE,
+            Transition::Custom(source) => source,
+        }
+    }
+
+    fn compile(&self) -> Result<Program, ShaderError> {
+        let src = self.source();
+        let top = format!(
+            "#include \"glrs/transition.glsl\"\n{}\nvoid main() {{\n    out_color = transition(v_uv);\n}}\n",
+            with_defaults(src)
+        );
+        let mut preprocessed = Preprocessor::default()
+            .builtin("glrs/transition.glsl", TRANSITION_GLSL)
+            .run(&top)
+            .map_err(|e| ShaderError::Compile {
+                stage: ShaderStage::Fragment,
+                source: src.to_string(),
+                log: vec![e.into()],
+            })?;
+
+        // Number the transition's lines from its own first line, and leave
+        // the lines added around it unmapped.
+        let lines = src.lines().count() as u32;
+        for entry in &mut preprocessed.line_map {
+            let wrapper = match entry {
+                Some(location) if location.file.is_none() => {
+                    location.line -= 1;
+                    location.line == 0 || location.line > lines
+                }
+                _ => false,
+            };
+            if wrapper {
+                *entry = None;
+            }
+        }
+
+        let vs = Shader::compile(VS_SRC, gl::VERTEX_SHADER)?;
+        let fs = Shader::from_preprocessed(src, &preprocessed, gl::FRAGMENT_SHADER)?;
+        Program::link(&vs, &fs)
+    }
+}
+
+// gl-transitions gives parameters as uniforms with their defaults in a
+// comment. Nothing here sets them, so each becomes a global holding its
+// default, on the same line to keep line numbers.
+fn with_defaults(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    for line in src.lines() {
+        let rewritten = line.split_once("// =").and_then(|(declaration, value)| {
+            let declaration = declaration.trim().strip_prefix("uniform ")?;
+            let (ty, name) = declaration.strip_suffix(';')?.trim().split_once(' ')?;
+            if ty.starts_with("sampler") {
+                return None;
+            }
+            let value = value.trim().trim_end_matches(';');
+            Some(format!("{} {} = {};", ty, name.trim(), value))
+        });
+        out.push_str(rewritten.as_deref().unwrap_or(line));
+        out.push('\n');
+    }
+    out
+}
+
+#[derive(Clone, Copy)]
+struct Entry {
+    create: CreateFn,
+    duration: f32,
+}
+
+/// Demos played one after another, each for a set time, with a transition
+/// between them. The last gives way to the first again.
+#[derive(Clone)]
+pub struct Playlist {
+    entries: Vec<Entry>,
+    transition: Transition,
+    transition_time: f32,
+}
+
+impl Default for Playlist {
+    fn default() -> Self {
+        Playlist::new()
+    }
+}
+
+impl Playlist {
+    /// An empty playlist with a two second crossfade.
+    pub fn new() -> Playlist {
+        Playlist {
+            entries: Vec::new(),
+            transition: Transition::Crossfade,
+            transition_time: 2.0,
+        }
+    }
+
+    /// Adds a demo that stays up for `duration` seconds, counted from the
+    /// start of the transition that brings it in.
+    pub fn add(mut self, create: CreateFn, duration: f32) -> Self {
+        self.entries.push(Entry { create, duration });
+        self
+    }
+
+    pub fn transition(mut self, transition: Transition, seconds: f32) -> Self {
+        self.transition = transition;
+        self.transition_time = seconds;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Compiles the transition and creates the first demo. Each demo gets
+    /// an RNG seeded from `rng`, so a seeded playlist is reproducible.
+    pub fn start(&self, mut rng: StdRng) -> Result<PlaylistPlayer, Box<dyn Error>> {
+        let first = self.entries.first().ok_or("the playlist is empty")?;
+        let program = self.transition.compile()?;
+        let demo = (first.create)(StdRng::seed_from_u64(rng.gen()))?;
+
+        let transition_time = match self.transition {
+            Transition::Cut => 0.0,
+            _ => self.transition_time.max(0.0),
+        };
+        Ok(PlaylistPlayer {
+            entries: self.entries.clone(),
+            transition_time,
+            program,
+            vao: VertexArray::new(),
+            rng,
+            current: Slot {
+                index: 0,
+                demo,
+                target: None,
+            },
+            next: None,
+            elapsed: 0.0,
+            width: 0,
+            height: 0,
+        })
+    }
+}
+
+struct Slot {
+    index: usize,
+    demo: Box<dyn Demo>,
+    // Made in `init`, once the size is known.
+    target: Option<Framebuffer>,
+}
+
+impl Slot {
+    fn init(&mut self, width: u32, height: u32) {
+        let output = Output::current();
+        self.target = new_target(width, height);
+        if let Some(target) = &self.target {
+            target.bind();
+        }
+        self.demo.init(width, height);
+        output.restore();
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        let output = Output::current();
+        self.target = new_target(width, height);
+        if let Some(target) = &self.target {
+            target.bind();
+        }
+        self.demo.resize(width, height);
+        output.restore();
+    }
+
+    fn render(&mut self, alpha: f32) {
+        if let Some(target) = &self.target {
+            target.bind();
+            app::reset_gl_state();
+            self.demo.render(alpha);
+        }
+    }
+}
+
+// Whatever was bound for the player to draw to, the window or a headless
+// framebuffer, kept while a slot binds its own.
+struct Output {
+    framebuffer: GLuint,
+    viewport: [GLint; 4],
+}
+
+impl Output {
+    fn current() -> Output {
+        let mut framebuffer = 0;
+        let mut viewport = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut framebuffer);
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+        }
+        Output {
+            framebuffer: framebuffer as GLuint,
+            viewport,
+        }
+    }
+
+    fn restore(&self) {
+        let [x, y, width, height] = self.viewport;
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+            gl::Viewport(x, y, width, height);
+        }
+    }
+}
+
+fn new_target(width: u32, height: u32) -> Option<Framebuffer> {
+    match Framebuffer::new(width as i32, height as i32) {
+        Ok(target) => Some(target),
+        Err(e) => {
+            eprintln!("{}", e);
+            None
+        }
+    }
+}
+
+/// A running `Playlist`. Each demo renders into a framebuffer of its own,
+/// and the transition shader draws the pair to the screen while one gives
+/// way to the next.
+pub struct PlaylistPlayer {
+    entries: Vec<Entry>,
+    transition_time: f32,
+    program: Program,
+    vao: VertexArray,
+    rng: StdRng,
+    current: Slot,
+    // The demo coming in, during a transition.
+    next: Option<Slot>,
+    // Seconds since the current demo, or the next one during a transition,
+    // came in.
+    elapsed: f32,
+    width: u32,
+    height: u32,
+}
+
+impl PlaylistPlayer {
+    fn start_next(&mut self) {
+        let index = (self.current.index + 1) % self.entries.len();
+        let create = self.entries[index].create;
+        match create(StdRng::seed_from_u64(self.rng.gen())) {
+            Ok(demo) => {
+                let mut next = Slot {
+                    index,
+                    demo,
+                    target: None,
+                };
+                next.init(self.width, self.height);
+                self.next = Some(next);
+            }
+            // Skip to the one after, keeping the current demo up meanwhile.
+            Err(e) => {
+                eprintln!("{}", e);
+                self.current.index = index;
+            }
+        }
+        self.elapsed = 0.0;
+    }
+
+    fn finish_transition(&mut self) {
+        if let Some(next) = self.next.take() {
+            let mut previous = std::mem::replace(&mut self.current, next);
+            previous.demo.destroy();
+        }
+    }
+}
+
+impl Demo for PlaylistPlayer {
+    fn init(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.current.init(width, height);
+    }
+
+    fn update(&mut self, dt: f32, input: &Input) {
+        self.elapsed += dt;
+        if self.next.is_none()
+            && self.entries.len() > 1
+            && self.elapsed >= self.entries[self.current.index].duration
+        {
+            self.start_next();
+        }
+        if self.next.is_some() && self.elapsed >= self.transition_time {
+            self.finish_transition();
+        }
+
+        self.current.demo.update(dt, input);
+        if let Some(next) = &mut self.next {
+            next.demo.update(dt, input);
+        }
+    }
+
+    fn render(&mut self, alpha: f32) {
+        let output = Output::current();
+        self.current.render(alpha);
+        if let Some(next) = &mut self.next {
+            next.render(alpha);
+        }
+        output.restore();
+        app::reset_gl_state();
+
+        let from = match &self.current.target {
+            Some(target) => target,
+            None => return,
+        };
+        let to = self.next.as_ref().and_then(|next| next.target.as_ref());
+        let progress = match to {
+            Some(_) => (self.elapsed / self.transition_time.max(f32::EPSILON)).min(1.0),
+            None => 0.0,
+        };
+
+        from.color().bind(0);
+        to.unwrap_or(from).color().bind(1);
+        self.program.bind();
+        // Transitions are free to ignore any of these.
+        self.program.set_if_active("u_from", 0);
+        self.program.set_if_active("u_to", 1);
+        self.program.set_if_active("progress", progress);
+        self.program
+            .set_if_active("ratio", self.width as f32 / self.height.max(1) as f32);
+        self.vao.bind();
+        unsafe {
+            gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        }
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.current.resize(width, height);
+        if let Some(next) = &mut self.next {
+            next.resize(width, height);
+        }
+    }
+
+    fn handle_event(&mut self, event: &WindowEvent) {
+        self.current.demo.handle_event(event);
+        if let Some(next) = &mut self.next {
+            next.demo.handle_event(event);
+        }
+    }
+
+    fn destroy(&mut self) {
+        self.current.demo.destroy();
+        if let Some(next) = &mut self.next {
+            next.demo.destroy();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless;
+
+    struct Blank;
+
+    impl Demo for Blank {
+        fn init(&mut self, _width: u32, _height: u32) {
+            unsafe { gl::Clear(gl::COLOR_BUFFER_BIT) };
+        }
+
+        fn render(&mut self, _alpha: f32) {}
+    }
+
+    fn blank(_rng: StdRng) -> Result<Box<dyn Demo>, Box<dyn Error>> {
+        Ok(Box::new(Blank))
+    }
+
+    fn bound() -> (GLint, [GLint; 4]) {
+        let mut framebuffer = 0;
+        let mut viewport = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut framebuffer);
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+        }
+        (framebuffer, viewport)
+    }
+
+    #[test]
+    fn slots_leave_the_output_bound() {
+        let Some(_gl) = headless::test_context() else {
+            return;
+        };
+
+        let output = Framebuffer::new(40, 30).unwrap();
+        output.bind();
+        let expected = (output.id() as GLint, [0, 0, 40, 30]);
+
+        let mut player = Playlist::new()
+            .add(blank, 1.0)
+            .add(blank, 1.0)
+            .start(StdRng::seed_from_u64(1))
+            .unwrap();
+        player.init(40, 30);
+        assert_eq!(bound(), expected);
+
+        player.update(1.0, &Input::new(40, 30));
+        assert!(player.next.is_some());
+        assert_eq!(bound(), expected);
+
+        player.resize(40, 30);
+        assert_eq!(bound(), expected);
+    }
+
+    #[test]
+    fn uniform_defaults_become_globals() {
+        let src = "uniform float amount; // = 0.5\n  uniform vec2 center; // = vec2(0.5, 0.5);\nuniform sampler2D map; // = 0\nvec4 transition(vec2 uv) {";
+        assert_eq!(
+            with_defaults(src),
+            "float amount = 0.5;\nvec2 center = vec2(0.5, 0.5);\nuniform sampler2D map; // = 0\nvec4 transition(vec2 uv) {\n"
+        );
+    }
+
+    #[test]
+    fn transitions_parse_by_name() {
+        assert_eq!("wipe".parse(), Ok(Transition::Wipe));
+        assert!("swirl".parse::<Transition>().is_err());
+    }
+}
@@ -0,0 +1,480 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_VERSION: &str = "330 core";
+
+/// `glrs/clip_space.glsl`, for shaders that work in pixels.
+static CLIP_SPACE_GLSL: &str = "vec2 pixel_to_clip(vec2 pixel, vec2 resolution) {
+    vec2 clip = (pixel / resolution) * 2.0 - 1.0;
+    return clip * vec2(1.0, -1.0);
+}
+";
+
+/// Includes every `Preprocessor` starts with, whatever the search path.
+/// Modules with includes of their own add them with `Preprocessor::builtin`.
+static BUILTIN_INCLUDES: &[(&str, &str)] = &[("glrs/clip_space.glsl", CLIP_SPACE_GLSL)];
+
+/// Where a line of preprocessed output came from. `file` is `None` for the
+/// top-level source handed to the preprocessor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: Option<String>,
+    pub line: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct PreprocessError {
+    pub location: SourceLocation,
+    pub message: String,
+}
+
+impl fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.location.file {
+            Some(file) => write!(f, "{}:{}: {}", file, self.location.line, self.message),
+            None => write!(f, "{}: {}", self.location.line, self.message),
+        }
+    }
+}
+
+impl Error for PreprocessError {}
+
+#[derive(Debug)]
+pub struct Preprocessed {
+    pub source: String,
+    /// One entry per output line; `None` for lines the preprocessor generated.
+    pub line_map: Vec<Option<SourceLocation>>,
+    /// Files read from disk while resolving includes.
+    pub includes: Vec<PathBuf>,
+}
+
+impl Preprocessed {
+    pub fn location(&self, line: u32) -> Option<&SourceLocation> {
+        let index = (line as usize).checked_sub(1)?;
+        self.line_map.get(index)?.as_ref()
+    }
+}
+
+#[derive(Clone)]
+pub struct Preprocessor {
+    version: String,
+    defines: Vec<(String, String)>,
+    search_paths: Vec<PathBuf>,
+    builtins: Vec<(String, Cow<'static, str>)>,
+}
+
+impl Default for Preprocessor {
+    fn default() -> Self {
+        Preprocessor {
+            version: DEFAULT_VERSION.to_string(),
+            defines: Vec::new(),
+            search_paths: vec![PathBuf::from("shaders")],
+            builtins: BUILTIN_INCLUDES
+                .iter()
+                .map(|&(name, src)| (name.to_string(), Cow::Borrowed(src)))
+                .collect(),
+        }
+    }
+}
+
+impl Preprocessor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The `#version` used when the source doesn't declare its own.
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = version.to_string();
+        self
+    }
+
+    pub fn define(mut self, name: &str, value: impl ToString) -> Self {
+        self.defines.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn search_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.search_paths.push(path.into());
+        self
+    }
+
+    /// Drops every search path, `shaders/` included, leaving only includes
+    /// relative to the including file and the built-in ones.
+    pub fn clear_search_paths(mut self) -> Self {
+        self.search_paths.clear();
+        self
+    }
+
+    /// Makes `src` includable as `name` when no file of that name is found
+    /// first, replacing any built-in include already called that.
+    pub fn builtin(mut self, name: &str, src: impl Into<Cow<'static, str>>) -> Self {
+        self.builtins.retain(|(builtin, _)| builtin != name);
+        self.builtins.push((name.to_string(), src.into()));
+        self
+    }
+
+    pub fn run(&self, src: &str) -> Result<Preprocessed, PreprocessError> {
+        let mut out = Output {
+            version: None,
+            lines: Vec::new(),
+            includes: Vec::new(),
+            once: Vec::new(),
+        };
+        let mut stack = Vec::new();
+        self.expand(src, None, None, &mut stack, &mut out)?;
+
+        let version = out.version.unwrap_or_else(|| self.version.clone());
+        let mut source = format!("#version {}\n", version);
+        let mut line_map = vec![None];
+
+        for (name, value) in &self.defines {
+            source.push_str(&format!("#define {} {}\n", name, value));
+            line_map.push(None);
+        }
+        for (text, location) in out.lines {
+            source.push_str(&text);
+            source.push('\n');
+            line_map.push(Some(location));
+        }
+
+        Ok(Preprocessed {
+            source,
+            line_map,
+            includes: out.includes,
+        })
+    }
+
+    fn expand(
+        &self,
+        src: &str,
+        file: Option<&str>,
+        dir: Option<&Path>,
+        stack: &mut Vec<String>,
+        out: &mut Output,
+    ) -> Result<(), PreprocessError> {
+        for (index, text) in src.lines().enumerate() {
+            let location = SourceLocation {
+                file: file.map(str::to_string),
+                line: index as u32 + 1,
+            };
+            let error = |message: String| PreprocessError {
+                location: location.clone(),
+                message,
+            };
+
+            let directive = text.trim_start();
+            if let Some(version) = directive.strip_prefix("#version") {
+                if out.version.is_none() {
+                    out.version = Some(version.trim().to_string());
+                }
+                // Keep the line count stable so the map stays one-to-one.
+                out.lines.push((String::new(), location));
+            } else if directive.starts_with("#pragma once") {
+                if let Some(file) = file {
+                    out.once.push(file.to_string());
+                }
+                out.lines.push((String::new(), location));
+            } else if let Some(rest) = directive
+                .strip_prefix("#include")
+                // Not `#include_next` or some other directive that starts alike.
+                .filter(|rest| {
+                    rest.is_empty()
+                        || rest.starts_with(['"', '<'])
+                        || rest.starts_with(char::is_whitespace)
+                })
+            {
+                let name = rest
+                    .trim()
+                    .strip_prefix('"')
+                    .and_then(|rest| rest.strip_suffix('"'))
+                    .ok_or_else(|| error(format!("malformed #include: {}", text.trim())))?;
+
+                let (key, contents, path) = self
+                    .resolve(name, dir)
+                    .ok_or_else(|| error(format!("cannot find include file \"{}\"", name)))?;
+
+                if stack.contains(&key) {
+                    return Err(error(format!("recursive include of \"{}\"", name)));
+                }
+                if out.once.contains(&key) {
+                    continue;
+                }
+
+                let include_dir = path
+                    .as_ref()
+                    .and_then(|p| p.parent())
+                    .map(Path::to_path_buf);
+                if let Some(path) = path {
+                    out.includes.push(path);
+                }
+
+                stack.push(key.clone());
+                self.expand(&contents, Some(&key), include_dir.as_deref(), stack, out)?;
+                stack.pop();
+            } else {
+                out.lines.push((text.to_string(), location));
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve(&self, name: &str, dir: Option<&Path>) -> Option<(String, String, Option<PathBuf>)> {
+        if Path::new(name).is_absolute() {
+            let path = PathBuf::from(name);
+            let contents = fs::read_to_string(&path).ok()?;
+            return Some((path.display().to_string(), contents, Some(path)));
+        }
+
+        for base in dir
+            .into_iter()
+            .chain(self.search_paths.iter().map(PathBuf::as_path))
+        {
+            let path = base.join(name);
+            if let Ok(contents) = fs::read_to_string(&path) {
+                return Some((path.display().to_string(), contents, Some(path)));
+            }
+        }
+
+        self.builtins
+            .iter()
+            .find(|(builtin, _)| builtin == name)
+            .map(|(builtin, contents)| (builtin.clone(), contents.to_string(), None))
+    }
+}
+
+struct Output {
+    version: Option<String>,
+    lines: Vec<(String, SourceLocation)>,
+    includes: Vec<PathBuf>,
+    once: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    // A scratch directory of shader files, removed when dropped.
+    struct Files(PathBuf);
+
+    impl Files {
+        fn new(name: &str) -> Files {
+            let root = env::temp_dir().join(format!("glrs-{}-{}", name, process::id()));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(&root).unwrap();
+            Files(root)
+        }
+
+        fn add(self, name: &str, contents: &str) -> Files {
+            let path = self.0.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+            self
+        }
+
+        fn preprocessor(&self) -> Preprocessor {
+            Preprocessor::new().search_path(&self.0)
+        }
+    }
+
+    impl Drop for Files {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    // The output without the `#version` line.
+    fn body(preprocessed: &Preprocessed) -> Vec<&str> {
+        preprocessed.source.lines().skip(1).collect()
+    }
+
+    #[test]
+    fn includes_resolve_next_to_the_includer_then_on_the_search_path() {
+        let files = Files::new("resolve")
+            .add("lib/outer.glsl", "#include \"common.glsl\"")
+            .add("lib/common.glsl", "next to outer")
+            .add("common.glsl", "on the search path");
+
+        let preprocessed = files
+            .preprocessor()
+            .run("#include \"lib/outer.glsl\"\n#include \"common.glsl\"")
+            .unwrap();
+        assert_eq!(body(&preprocessed), ["next to outer", "on the search path"]);
+        assert_eq!(preprocessed.includes.len(), 3);
+    }
+
+    #[test]
+    fn builtins_come_after_files_on_disk() {
+        let builtin = Preprocessor::new()
+            .clear_search_paths()
+            .run("#include \"glrs/clip_space.glsl\"")
+            .unwrap();
+        assert!(builtin.source.contains("vec2 pixel_to_clip("));
+        assert!(builtin.includes.is_empty());
+
+        let files = Files::new("shadow").add("glrs/clip_space.glsl", "// from disk");
+        let shadowed = files
+            .preprocessor()
+            .run("#include \"glrs/clip_space.glsl\"")
+            .unwrap();
+        assert_eq!(body(&shadowed), ["// from disk"]);
+
+        let missing = Preprocessor::new()
+            .clear_search_paths()
+            .run("\n#include \"nope.glsl\"")
+            .unwrap_err();
+        assert_eq!(missing.location.line, 2);
+        assert!(missing.message.contains("cannot find"));
+    }
+
+    #[test]
+    fn builtins_can_be_added_and_replaced() {
+        let preprocessor = Preprocessor::new()
+            .clear_search_paths()
+            .builtin("mine.glsl", "// mine")
+            .builtin("glrs/clip_space.glsl", String::from("// replaced"));
+        let out = preprocessor
+            .run("#include \"mine.glsl\"\n#include \"glrs/clip_space.glsl\"")
+            .unwrap();
+        assert_eq!(body(&out), ["// mine", "// replaced"]);
+        assert_eq!(
+            out.location(2),
+            Some(&SourceLocation {
+                file: Some("mine.glsl".to_string()),
+                line: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn include_must_be_a_whole_directive() {
+        let files = Files::new("directive").add("x.glsl", "included");
+        let preprocessor = files.preprocessor();
+
+        let passed = preprocessor
+            .run("#include_next \"x.glsl\"\n#includefoo\n#include\"x.glsl\"")
+            .unwrap();
+        assert_eq!(
+            body(&passed),
+            ["#include_next \"x.glsl\"", "#includefoo", "included"]
+        );
+
+        let error = preprocessor.run("#include <x.glsl>").unwrap_err();
+        assert!(error.message.contains("malformed #include"));
+    }
+
+    #[test]
+    fn absolute_includes_need_no_search_path() {
+        let files = Files::new("absolute").add("abs.glsl", "absolute");
+        let name = files.0.join("abs.glsl");
+        let preprocessed = Preprocessor::new()
+            .clear_search_paths()
+            .run(&format!("#include \"{}\"", name.display()))
+            .unwrap();
+        assert_eq!(body(&preprocessed), ["absolute"]);
+        assert_eq!(preprocessed.includes, [name]);
+    }
+
+    #[test]
+    fn pragma_once_includes_a_file_once() {
+        let files = Files::new("once")
+            .add("once.glsl", "#pragma once\nshared")
+            .add(
+                "both.glsl",
+                "#include \"once.glsl\"\n#include \"once.glsl\"",
+            );
+
+        let preprocessed = files.preprocessor().run("#include \"both.glsl\"").unwrap();
+        assert_eq!(body(&preprocessed), ["", "shared"]);
+    }
+
+    #[test]
+    fn recursive_includes_are_errors() {
+        let files = Files::new("recursive")
+            .add("a.glsl", "#include \"b.glsl\"")
+            .add("b.glsl", "// b\n#include \"a.glsl\"");
+
+        let error = files.preprocessor().run("#include \"a.glsl\"").unwrap_err();
+        assert!(error.message.contains("recursive include"));
+        assert!(error.location.file.unwrap().ends_with("b.glsl"));
+        assert_eq!(error.location.line, 2);
+    }
+
+    #[test]
+    fn version_is_hoisted_and_overrides_the_default() {
+        let hoisted = Preprocessor::new()
+            .version("300 es")
+            .run("float x;\n#version 450\nfloat y;")
+            .unwrap();
+        assert_eq!(
+            hoisted.source.lines().collect::<Vec<_>>(),
+            ["#version 450", "float x;", "", "float y;"]
+        );
+
+        let default = Preprocessor::new().version("300 es").run("").unwrap();
+        assert_eq!(default.source, "#version 300 es\n");
+    }
+
+    #[test]
+    fn defines_follow_the_version_in_order() {
+        let preprocessed = Preprocessor::new()
+            .define("B", 2)
+            .define("A", "1.0")
+            .run("#version 410\nvoid main() {}")
+            .unwrap();
+        assert_eq!(
+            preprocessed.source.lines().collect::<Vec<_>>(),
+            [
+                "#version 410",
+                "#define B 2",
+                "#define A 1.0",
+                "",
+                "void main() {}"
+            ]
+        );
+        assert_eq!(preprocessed.line_map[..3], [None, None, None]);
+    }
+
+    #[test]
+    fn line_map_follows_nested_includes() {
+        let files = Files::new("line-map")
+            .add(
+                "outer.glsl",
+                "// outer 1\n#include \"inner.glsl\"\n// outer 3",
+            )
+            .add("inner.glsl", "// inner 1\n// inner 2");
+
+        let preprocessed = files
+            .preprocessor()
+            .define("X", 1)
+            .run("// top 1\n#include \"outer.glsl\"\n// top 3")
+            .unwrap();
+        let at = |line| {
+            let location = preprocessed.location(line)?;
+            let file = location.file.as_deref().map(|file| {
+                Path::new(file)
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            });
+            Some((file, location.line))
+        };
+
+        // `#version` and the define come first and map nowhere.
+        assert_eq!(at(1), None);
+        assert_eq!(at(2), None);
+        assert_eq!(at(3), Some((None, 1)));
+        assert_eq!(at(4), Some((Some("outer.glsl".to_string()), 1)));
+        assert_eq!(at(5), Some((Some("inner.glsl".to_string()), 1)));
+        assert_eq!(at(6), Some((Some("inner.glsl".to_string()), 2)));
+        assert_eq!(at(7), Some((Some("outer.glsl".to_string()), 3)));
+        assert_eq!(at(8), Some((None, 3)));
+        assert_eq!(at(9), None);
+        assert_eq!(preprocessed.source.lines().nth(5), Some("// inner 2"));
+    }
+}
@@ -0,0 +1,475 @@
+extern crate gl;
+use super::shader::{self, Shader, ShaderError};
+use gl::types::*;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+/// An active uniform or attribute as reported by the driver after linking.
+#[derive(Debug, Clone)]
+pub struct Variable {
+    pub name: String,
+    pub location: GLint,
+    pub ty: GLenum,
+    pub size: GLint,
+}
+
+/// A linked program with its active uniforms and attributes read once up
+/// front, so lookups don't hit the driver and typos get reported. The GL
+/// program is deleted on drop.
+pub struct Program {
+    id: GLuint,
+    uniforms: HashMap<String, Variable>,
+    attributes: HashMap<String, Variable>,
+    warned: RefCell<HashSet<String>>,
+}
+
+impl Program {
+    pub fn link(vs: &Shader, fs: &Shader) -> Result<Program, ShaderError> {
+        Ok(Program::from_id(shader::link_program(vs.id(), fs.id())?))
+    }
+
+    /// Takes ownership of an already linked program.
+    pub fn from_id(id: GLuint) -> Program {
+        unsafe {
+            Program {
+                id,
+                uniforms: reflect(id, gl::ACTIVE_UNIFORMS, gl::ACTIVE_UNIFORM_MAX_LENGTH),
+                attributes: reflect(id, gl::ACTIVE_ATTRIBUTES, gl::ACTIVE_ATTRIBUTE_MAX_LENGTH),
+                warned: RefCell::new(HashSet::new()),
+            }
+        }
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::UseProgram(self.id);
+        }
+    }
+
+    pub fn uniforms(&self) -> impl Iterator<Item = &Variable> {
+        self.uniforms.values()
+    }
+
+    pub fn attributes(&self) -> impl Iterator<Item = &Variable> {
+        self.attributes.values()
+    }
+
+    pub fn uniform(&self, name: &str) -> Option<&Variable> {
+        let found = self.uniforms.get(name);
+        if found.is_none() {
+            self.warn_unknown("uniform", name, &self.uniforms);
+        }
+        found
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&Variable> {
+        let found = self.attributes.get(name);
+        if found.is_none() {
+            self.warn_unknown("attribute", name, &self.attributes);
+        }
+        found
+    }
+
+    pub fn attribute_location(&self, name: &str) -> Option<GLuint> {
+        self.attribute(name).map(|a| a.location as GLuint)
+    }
+
+    /// Binds the program and sets `name` to `value`. Unknown names, values
+    /// that don't match the uniform's GLSL type and slices longer than the
+    /// uniform array are reported once and otherwise ignored.
+    pub fn set<T: Uniform>(&self, name: &str, value: T) {
+        let uniform = match self.uniform(name) {
+            Some(uniform) => uniform,
+            None => return,
+        };
+
+        if let Err(message) = check(uniform, name, &value) {
+            self.warn_once(name, message);
+            return;
+        }
+
+        unsafe {
+            gl::UseProgram(self.id);
+            value.apply(uniform.location);
+        }
+    }
+
+    /// Like `set`, but quietly does nothing when the program doesn't use
+    /// `name`, for uniforms a shader is free to leave out.
+    pub fn set_if_active<T: Uniform>(&self, name: &str, value: T) {
+        if self.uniforms.contains_key(name) {
+            self.set(name, value);
+        }
+    }
+
+    fn warn_unknown(&self, kind: &str, name: &str, known: &HashMap<String, Variable>) {
+        let mut message = format!(
+            "program {} has no active {} `{}` (misspelled or optimized out?)",
+            self.id, kind, name
+        );
+        if let Some(k) = suggestion(known.keys(), name) {
+            message.push_str(&format!(", did you mean `{}`?", k));
+        }
+        self.warn_once(name, message);
+    }
+
+    fn warn_once(&self, key: &str, message: String) {
+        if self.warned.borrow_mut().insert(key.to_string()) {
+            eprintln!("warning: {}", message);
+        }
+    }
+}
+
+impl Drop for Program {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgram(self.id);
+        }
+    }
+}
+
+unsafe fn reflect(program: GLuint, count: GLenum, max_length: GLenum) -> HashMap<String, Variable> {
+    let mut n = 0;
+    gl::GetProgramiv(program, count, &mut n);
+    let mut len = 0;
+    gl::GetProgramiv(program, max_length, &mut len);
+
+    let mut variables = HashMap::new();
+    let mut buf = vec![0u8; len.max(1) as usize];
+
+    for index in 0..n as GLuint {
+        let mut written = 0;
+        let mut size = 0;
+        let mut ty = 0;
+        let name_ptr = buf.as_mut_ptr() as *mut GLchar;
+
+        let location = if count == gl::ACTIVE_UNIFORMS {
+            gl::GetActiveUniform(
+                program,
+                index,
+                len,
+                &mut written,
+                &mut size,
+                &mut ty,
+                name_ptr,
+            );
+            gl::GetUniformLocation(program, name_ptr)
+        } else {
+            gl::GetActiveAttrib(
+                program,
+                index,
+                len,
+                &mut written,
+                &mut size,
+                &mut ty,
+                name_ptr,
+            );
+            gl::GetAttribLocation(program, name_ptr)
+        };
+
+        let name = String::from_utf8_lossy(&buf[..written as usize]).into_owned();
+        // Arrays are reported as `name[0]`; accept the bare name as well.
+        let bare = name.strip_suffix("[0]").map(str::to_string);
+        let variable = Variable {
+            name: name.clone(),
+            location,
+            ty,
+            size,
+        };
+
+        if let Some(bare) = bare {
+            variables.insert(bare, variable.clone());
+        }
+        variables.insert(name, variable);
+    }
+
+    variables
+}
+
+// Why `value` can't be uploaded to `uniform`, if it can't.
+fn check<T: Uniform>(uniform: &Variable, name: &str, value: &T) -> Result<(), String> {
+    if !T::accepts(uniform.ty) {
+        return Err(format!(
+            "uniform `{}` is a {}, can't set it from a {}",
+            name,
+            type_name(uniform.ty),
+            std::any::type_name::<T>()
+        ));
+    }
+    if value.count() > uniform.size.max(1) as usize {
+        return Err(format!(
+            "uniform `{}` holds {} elements, can't set {}",
+            name,
+            uniform.size,
+            value.count()
+        ));
+    }
+    Ok(())
+}
+
+// The closest of `known` to `name`, if any is a likely typo of it.
+fn suggestion<'a>(known: impl Iterator<Item = &'a String>, name: &str) -> Option<&'a str> {
+    known
+        .map(|k| (edit_distance(k, name), k))
+        .filter(|(d, _)| *d <= 2)
+        .min()
+        .map(|(_, k)| k.as_str())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { prev } else { prev + 1 };
+            prev = row[j + 1];
+            row[j + 1] = cost.min(row[j] + 1).min(prev + 1);
+        }
+    }
+    row[b.len()]
+}
+
+pub fn type_name(ty: GLenum) -> &'static str {
+    match ty {
+        gl::FLOAT => "float",
+        gl::FLOAT_VEC2 => "vec2",
+        gl::FLOAT_VEC3 => "vec3",
+        gl::FLOAT_VEC4 => "vec4",
+        gl::INT => "int",
+        gl::INT_VEC2 => "ivec2",
+        gl::INT_VEC3 => "ivec3",
+        gl::INT_VEC4 => "ivec4",
+        gl::UNSIGNED_INT => "uint",
+        gl::BOOL => "bool",
+        gl::FLOAT_MAT2 => "mat2",
+        gl::FLOAT_MAT3 => "mat3",
+        gl::FLOAT_MAT4 => "mat4",
+        gl::SAMPLER_1D => "sampler1D",
+        gl::SAMPLER_2D => "sampler2D",
+        gl::SAMPLER_3D => "sampler3D",
+        gl::SAMPLER_CUBE => "samplerCube",
+        _ => "unknown type",
+    }
+}
+
+fn is_sampler(ty: GLenum) -> bool {
+    matches!(
+        ty,
+        gl::SAMPLER_1D
+            | gl::SAMPLER_2D
+            | gl::SAMPLER_3D
+            | gl::SAMPLER_CUBE
+            | gl::SAMPLER_2D_ARRAY
+            | gl::SAMPLER_2D_SHADOW
+            | gl::INT_SAMPLER_2D
+            | gl::UNSIGNED_INT_SAMPLER_2D
+    )
+}
+
+/// A Rust value that can be uploaded to a uniform of a matching GLSL type.
+pub trait Uniform {
+    fn accepts(ty: GLenum) -> bool;
+
+    /// Array elements `apply` uploads.
+    fn count(&self) -> usize {
+        1
+    }
+
+    /// # Safety
+    /// The program owning `location` must be current.
+    unsafe fn apply(&self, location: GLint);
+}
+
+impl Uniform for f32 {
+    fn accepts(ty: GLenum) -> bool {
+        ty == gl::FLOAT
+    }
+
+    unsafe fn apply(&self, location: GLint) {
+        gl::Uniform1f(location, *self);
+    }
+}
+
+impl Uniform for [f32; 2] {
+    fn accepts(ty: GLenum) -> bool {
+        ty == gl::FLOAT_VEC2
+    }
+
+    unsafe fn apply(&self, location: GLint) {
+        gl::Uniform2fv(location, 1, self.as_ptr());
+    }
+}
+
+impl Uniform for [f32; 3] {
+    fn accepts(ty: GLenum) -> bool {
+        ty == gl::FLOAT_VEC3
+    }
+
+    unsafe fn apply(&self, location: GLint) {
+        gl::Uniform3fv(location, 1, self.as_ptr());
+    }
+}
+
+impl Uniform for [f32; 4] {
+    fn accepts(ty: GLenum) -> bool {
+        ty == gl::FLOAT_VEC4
+    }
+
+    unsafe fn apply(&self, location: GLint) {
+        gl::Uniform4fv(location, 1, self.as_ptr());
+    }
+}
+
+impl Uniform for [[f32; 4]; 4] {
+    fn accepts(ty: GLenum) -> bool {
+        ty == gl::FLOAT_MAT4
+    }
+
+    unsafe fn apply(&self, location: GLint) {
+        gl::UniformMatrix4fv(location, 1, gl::FALSE, self.as_ptr() as *const GLfloat);
+    }
+}
+
+impl Uniform for i32 {
+    fn accepts(ty: GLenum) -> bool {
+        ty == gl::INT || ty == gl::BOOL || is_sampler(ty)
+    }
+
+    unsafe fn apply(&self, location: GLint) {
+        gl::Uniform1i(location, *self);
+    }
+}
+
+impl Uniform for [i32; 2] {
+    fn accepts(ty: GLenum) -> bool {
+        ty == gl::INT_VEC2
+    }
+
+    unsafe fn apply(&self, location: GLint) {
+        gl::Uniform2iv(location, 1, self.as_ptr());
+    }
+}
+
+impl Uniform for u32 {
+    fn accepts(ty: GLenum) -> bool {
+        ty == gl::UNSIGNED_INT
+    }
+
+    unsafe fn apply(&self, location: GLint) {
+        gl::Uniform1ui(location, *self);
+    }
+}
+
+impl Uniform for bool {
+    fn accepts(ty: GLenum) -> bool {
+        ty == gl::BOOL
+    }
+
+    unsafe fn apply(&self, location: GLint) {
+        gl::Uniform1i(location, *self as GLint);
+    }
+}
+
+/// Uploads a whole `float[]` uniform array.
+impl Uniform for &[f32] {
+    fn accepts(ty: GLenum) -> bool {
+        ty == gl::FLOAT
+    }
+
+    fn count(&self) -> usize {
+        self.len()
+    }
+
+    unsafe fn apply(&self, location: GLint) {
+        gl::Uniform1fv(location, self.len() as GLsizei, self.as_ptr());
+    }
+}
+
+/// Uploads a whole `vec3[]` uniform array.
+impl Uniform for &[[f32; 3]] {
+    fn accepts(ty: GLenum) -> bool {
+        ty == gl::FLOAT_VEC3
+    }
+
+    fn count(&self) -> usize {
+        self.len()
+    }
+
+    unsafe fn apply(&self, location: GLint) {
+        gl::Uniform3fv(
+            location,
+            self.len() as GLsizei,
+            self.as_ptr() as *const GLfloat,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(ty: GLenum, size: GLint) -> Variable {
+        Variable {
+            name: "u".to_string(),
+            location: 0,
+            ty,
+            size,
+        }
+    }
+
+    #[test]
+    fn values_must_match_the_glsl_type() {
+        assert!(check(&variable(gl::FLOAT_VEC2, 1), "u", &[0.0f32; 2]).is_ok());
+        assert!(check(&variable(gl::SAMPLER_2D, 1), "u", &0i32).is_ok());
+        assert!(check(&variable(gl::BOOL, 1), "u", &true).is_ok());
+
+        let error = check(&variable(gl::FLOAT_VEC3, 1), "u", &[0.0f32; 2]).unwrap_err();
+        assert!(error.starts_with("uniform `u` is a vec3, can't set it from a [f32; 2]"));
+        assert!(check(&variable(gl::INT, 1), "u", &0u32).is_err());
+    }
+
+    #[test]
+    fn slices_must_fit_the_array() {
+        let array = variable(gl::FLOAT, 4);
+        assert!(check(&array, "u", &&[0.0f32; 4][..]).is_ok());
+        assert!(check(&array, "u", &&[0.0f32; 2][..]).is_ok());
+        assert_eq!(
+            check(&array, "u", &&[0.0f32; 5][..]),
+            Err("uniform `u` holds 4 elements, can't set 5".to_string())
+        );
+        assert!(check(&variable(gl::FLOAT_VEC3, 2), "u", &&[[0.0f32; 3]; 3][..]).is_err());
+    }
+
+    #[test]
+    fn edit_distance_counts_single_character_edits() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("u_time", "u_time"), 0);
+        assert_eq!(edit_distance("u_time", "u_tme"), 1);
+        assert_eq!(edit_distance("u_time", "u_tine"), 1);
+        assert_eq!(edit_distance("u_time", "u_timer"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn suggestions_are_close_matches_only() {
+        let known: Vec<String> = ["u_time", "u_resolution", "u_mouse"]
+            .iter()
+            .map(|k| k.to_string())
+            .collect();
+        assert_eq!(suggestion(known.iter(), "u_tmie"), Some("u_time"));
+        assert_eq!(
+            suggestion(known.iter(), "u_resolutoin"),
+            Some("u_resolution")
+        );
+        assert_eq!(suggestion(known.iter(), "iResolution"), None);
+        assert_eq!(suggestion([].iter(), "u_time"), None);
+    }
+}
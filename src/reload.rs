@@ -0,0 +1,291 @@
+extern crate gl;
+use super::preprocess::Preprocessor;
+use super::program::Program;
+use super::shader::{Shader, ShaderError, ShaderStage};
+use gl::types::*;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+thread_local! {
+    // What `ShaderFile`s made on this thread start with for `builtin_only`.
+    static BUILTIN_ONLY: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Makes `ShaderFile`s created on this thread built-in only until the
+/// returned guard is dropped. For `headless::run`, which can't reach the
+/// files a demo makes.
+pub(crate) fn builtin_shaders_on_this_thread(builtin: bool) -> BuiltinShaders {
+    BuiltinShaders(BUILTIN_ONLY.with(|only| only.replace(builtin)))
+}
+
+pub(crate) struct BuiltinShaders(bool);
+
+impl Drop for BuiltinShaders {
+    fn drop(&mut self) {
+        BUILTIN_ONLY.with(|only| only.set(self.0));
+    }
+}
+
+/// A shader stage loaded from `path` when it exists, or from the built-in
+/// `fallback` source otherwise. Files pulled in with `#include` are watched
+/// along with it.
+pub struct ShaderFile {
+    // `None` for a stage built from source alone.
+    path: Option<PathBuf>,
+    fallback: Cow<'static, str>,
+    modified: Option<SystemTime>,
+    includes: Vec<(PathBuf, Option<SystemTime>)>,
+    builtins: Vec<(String, Cow<'static, str>)>,
+    builtin_only: bool,
+}
+
+impl ShaderFile {
+    pub fn new(path: impl Into<PathBuf>, fallback: impl Into<Cow<'static, str>>) -> Self {
+        ShaderFile {
+            path: Some(path.into()),
+            fallback: fallback.into(),
+            modified: None,
+            includes: Vec::new(),
+            builtins: Vec::new(),
+            builtin_only: BUILTIN_ONLY.with(Cell::get),
+        }
+    }
+
+    /// A stage built from `src` alone, with no file that can replace it.
+    /// Files it includes are still watched.
+    pub fn source(src: impl Into<Cow<'static, str>>) -> Self {
+        ShaderFile {
+            path: None,
+            fallback: src.into(),
+            modified: None,
+            includes: Vec::new(),
+            builtins: Vec::new(),
+            builtin_only: BUILTIN_ONLY.with(Cell::get),
+        }
+    }
+
+    /// Makes `src` includable as `name`, as with `Preprocessor::builtin`.
+    pub fn builtin(mut self, name: &str, src: impl Into<Cow<'static, str>>) -> Self {
+        self.builtins.push((name.to_string(), src.into()));
+        self
+    }
+
+    /// Compiles the built-in source and looks for includes among the
+    /// built-in ones only, whatever is on disk, so that local shader edits
+    /// can't change what it renders.
+    pub fn builtin_only(mut self) -> Self {
+        self.builtin_only = true;
+        self
+    }
+
+    // True when there is a file on disk to fall back from.
+    fn on_disk(&self) -> bool {
+        !self.builtin_only && self.path.as_deref().and_then(mtime).is_some()
+    }
+
+    // The files the last compile read, for messages.
+    fn files(&self) -> impl Iterator<Item = &Path> {
+        let path = self.path.as_deref().filter(|_| self.modified.is_some());
+        path.into_iter()
+            .chain(self.includes.iter().map(|(path, _)| path.as_path()))
+    }
+
+    fn changed(&self) -> bool {
+        !self.builtin_only
+            && (self.path.as_deref().and_then(mtime) != self.modified
+                || self.includes.iter().any(|(path, m)| mtime(path) != *m))
+    }
+
+    fn compile(&mut self, ty: GLenum, use_fallback: bool) -> Result<Shader, ShaderError> {
+        let use_fallback = use_fallback || self.builtin_only;
+        self.modified = self
+            .path
+            .as_deref()
+            .filter(|_| !self.builtin_only)
+            .and_then(mtime);
+        let src = match self
+            .path
+            .as_ref()
+            .filter(|_| self.modified.is_some() && !use_fallback)
+        {
+            Some(path) => fs::read_to_string(path).unwrap_or_else(|_| self.fallback.to_string()),
+            None => self.fallback.to_string(),
+        };
+
+        let mut preprocessor = Preprocessor::default();
+        if self.builtin_only {
+            preprocessor = preprocessor.clear_search_paths();
+        } else if let Some(dir) = self.path.as_deref().and_then(Path::parent) {
+            preprocessor = preprocessor.search_path(dir);
+        }
+        for (name, src) in &self.builtins {
+            preprocessor = preprocessor.builtin(name, src.clone());
+        }
+
+        let preprocessed = preprocessor.run(&src).map_err(|e| ShaderError::Compile {
+            stage: ShaderStage::from_gl(ty),
+            source: src.clone(),
+            log: vec![e.into()],
+        })?;
+
+        self.includes = preprocessed
+            .includes
+            .iter()
+            .map(|path| (path.clone(), mtime(path)))
+            .collect();
+
+        Shader::from_preprocessed(&src, &preprocessed, ty)
+    }
+}
+
+fn mtime(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// A vertex/fragment program that relinks itself when its source files
+/// change on disk. A failed reload is reported and the last working program
+/// stays in use.
+pub struct ReloadableProgram {
+    vertex: ShaderFile,
+    fragment: ShaderFile,
+    program: Program,
+    last_poll: Instant,
+}
+
+impl ReloadableProgram {
+    pub fn new(mut vertex: ShaderFile, mut fragment: ShaderFile) -> Result<Self, ShaderError> {
+        let program = match build(&mut vertex, &mut fragment, false) {
+            Ok(program) => program,
+            Err(e) if vertex.on_disk() || fragment.on_disk() => {
+                eprintln!("{}falling back to the built-in shaders", e);
+                build(&mut vertex, &mut fragment, true)?
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(ReloadableProgram {
+            vertex,
+            fragment,
+            program,
+            last_poll: Instant::now(),
+        })
+    }
+
+    /// Checks the source files for changes and relinks if needed. Returns
+    /// true when a new program was swapped in, in which case uniforms must be
+    /// set again and any attribute locations looked up again.
+    pub fn poll(&mut self) -> bool {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = Instant::now();
+
+        if !self.vertex.changed() && !self.fragment.changed() {
+            return false;
+        }
+
+        match build(&mut self.vertex, &mut self.fragment, false) {
+            Ok(program) => {
+                program.bind();
+                self.program = program;
+                let files: Vec<_> = self
+                    .vertex
+                    .files()
+                    .chain(self.fragment.files())
+                    .map(|path| path.display().to_string())
+                    .collect();
+                eprintln!("reloaded {}", files.join(", "));
+                true
+            }
+            Err(e) => {
+                eprintln!("{}keeping the previous program", e);
+                false
+            }
+        }
+    }
+}
+
+impl Deref for ReloadableProgram {
+    type Target = Program;
+
+    fn deref(&self) -> &Program {
+        &self.program
+    }
+}
+
+fn build(
+    vertex: &mut ShaderFile,
+    fragment: &mut ShaderFile,
+    use_fallback: bool,
+) -> Result<Program, ShaderError> {
+    let vs = vertex.compile(gl::VERTEX_SHADER, use_fallback)?;
+    let fs = fragment.compile(gl::FRAGMENT_SHADER, use_fallback)?;
+    Program::link(&vs, &fs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless;
+    use std::env;
+    use std::process;
+
+    const VS: &str = "void main() {\n    gl_Position = vec4(0.0);\n}\n";
+
+    #[test]
+    fn source_only_stages_watch_what_they_include() {
+        let Some(_gl) = headless::test_context() else {
+            return;
+        };
+
+        let path = env::temp_dir().join(format!("glrs-reload-{}.glsl", process::id()));
+        fs::write(&path, "vec4 color() {\n    return vec4(1.0);\n}\n").unwrap();
+        let fragment = format!(
+            "#include \"{}\"\nout vec4 out_color;\nvoid main() {{\n    out_color = color();\n}}\n",
+            path.display()
+        );
+        let program =
+            ReloadableProgram::new(ShaderFile::source(VS), ShaderFile::source(fragment)).unwrap();
+        assert!(!program.fragment.on_disk());
+        assert_eq!(program.vertex.files().count(), 0);
+        assert_eq!(program.fragment.files().collect::<Vec<_>>(), [&*path]);
+
+        // Nothing to fall back to, so a broken include is an error.
+        fs::write(&path, "vec4 color() {\n    return nope;\n}\n").unwrap();
+        let broken = format!("#include \"{}\"\nvoid main() {{}}\n", path.display());
+        let result = ReloadableProgram::new(ShaderFile::source(VS), ShaderFile::source(broken));
+        fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn builtin_only_ignores_files_on_disk() {
+        let Some(_gl) = headless::test_context() else {
+            return;
+        };
+
+        let path = env::temp_dir().join(format!("glrs-builtin-{}.vert", process::id()));
+        fs::write(&path, "this is not GLSL\n").unwrap();
+        let vertex = || ShaderFile::new(&path, VS);
+        let fragment = || ShaderFile::source("out vec4 c;\nvoid main() {\n    c = vec4(1.0);\n}\n");
+
+        let program = ReloadableProgram::new(vertex().builtin_only(), fragment()).unwrap();
+        assert!(!program.vertex.on_disk());
+        assert!(!program.vertex.changed());
+        assert_eq!(program.vertex.files().count(), 0);
+
+        // Only files made inside the scope start out built-in only.
+        {
+            let _builtin = builtin_shaders_on_this_thread(true);
+            assert!(vertex().builtin_only);
+        }
+        assert!(!vertex().builtin_only);
+        fs::remove_file(&path).unwrap();
+    }
+}
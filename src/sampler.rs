@@ -0,0 +1,305 @@
+extern crate gl;
+use gl::types::*;
+use std::ffi::{c_char, CStr};
+use std::sync::Mutex;
+
+// From EXT_texture_filter_anisotropic (core in 4.6); the generated bindings
+// stop at 4.5.
+const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
+
+// `max_anisotropy` for the current context, looked up on first use.
+static MAX_ANISOTROPY: Mutex<Option<Option<f32>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Wrap {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    /// Samples outside the texture return the given color.
+    ClampToBorder([f32; 4]),
+}
+
+impl Wrap {
+    fn to_gl(self) -> GLenum {
+        match self {
+            Wrap::Repeat => gl::REPEAT,
+            Wrap::MirroredRepeat => gl::MIRRORED_REPEAT,
+            Wrap::ClampToEdge => gl::CLAMP_TO_EDGE,
+            Wrap::ClampToBorder(_) => gl::CLAMP_TO_BORDER,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    Nearest,
+    Linear,
+}
+
+/// How a texture is sampled. Applied either to the texture itself or to a
+/// standalone `Sampler`, which then overrides the texture's own state on
+/// whichever unit it's bound to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SamplerOptions {
+    pub wrap_s: Wrap,
+    pub wrap_t: Wrap,
+    pub min_filter: Filter,
+    pub mag_filter: Filter,
+    /// How to pick between mip levels; `None` only samples the base level.
+    pub mipmap_filter: Option<Filter>,
+    /// Maximum anisotropy, clamped to what the driver supports. 1 disables it.
+    pub anisotropy: f32,
+}
+
+impl Default for SamplerOptions {
+    fn default() -> Self {
+        SamplerOptions {
+            wrap_s: Wrap::Repeat,
+            wrap_t: Wrap::Repeat,
+            min_filter: Filter::Linear,
+            mag_filter: Filter::Linear,
+            mipmap_filter: Some(Filter::Linear),
+            anisotropy: 1.0,
+        }
+    }
+}
+
+impl SamplerOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap_s = wrap;
+        self.wrap_t = wrap;
+        self
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.min_filter = filter;
+        self.mag_filter = filter;
+        self
+    }
+
+    pub fn mipmap_filter(mut self, filter: Option<Filter>) -> Self {
+        self.mipmap_filter = filter;
+        self
+    }
+
+    pub fn anisotropy(mut self, anisotropy: f32) -> Self {
+        self.anisotropy = anisotropy;
+        self
+    }
+
+    fn min_filter_gl(&self) -> GLenum {
+        match (self.min_filter, self.mipmap_filter) {
+            (Filter::Nearest, None) => gl::NEAREST,
+            (Filter::Linear, None) => gl::LINEAR,
+            (Filter::Nearest, Some(Filter::Nearest)) => gl::NEAREST_MIPMAP_NEAREST,
+            (Filter::Nearest, Some(Filter::Linear)) => gl::NEAREST_MIPMAP_LINEAR,
+            (Filter::Linear, Some(Filter::Nearest)) => gl::LINEAR_MIPMAP_NEAREST,
+            (Filter::Linear, Some(Filter::Linear)) => gl::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+
+    fn mag_filter_gl(&self) -> GLenum {
+        match self.mag_filter {
+            Filter::Nearest => gl::NEAREST,
+            Filter::Linear => gl::LINEAR,
+        }
+    }
+
+    /// Sets the parameters on the texture bound to `target` on the active
+    /// unit.
+    pub(crate) fn apply_to_texture(&self, target: GLenum) {
+        self.apply(
+            |pname, value| unsafe { gl::TexParameteri(target, pname, value) },
+            |pname, value| unsafe { gl::TexParameterf(target, pname, value) },
+            |pname, value| unsafe { gl::TexParameterfv(target, pname, value.as_ptr()) },
+        );
+    }
+
+    fn apply_to_sampler(&self, sampler: GLuint) {
+        self.apply(
+            |pname, value| unsafe { gl::SamplerParameteri(sampler, pname, value) },
+            |pname, value| unsafe { gl::SamplerParameterf(sampler, pname, value) },
+            |pname, value| unsafe { gl::SamplerParameterfv(sampler, pname, value.as_ptr()) },
+        );
+    }
+
+    fn apply(
+        &self,
+        int: impl Fn(GLenum, GLint),
+        float: impl Fn(GLenum, GLfloat),
+        floats: impl Fn(GLenum, &[GLfloat; 4]),
+    ) {
+        int(gl::TEXTURE_WRAP_S, self.wrap_s.to_gl() as GLint);
+        int(gl::TEXTURE_WRAP_T, self.wrap_t.to_gl() as GLint);
+        // There's a single border color, so an explicit one on T wins.
+        for wrap in [self.wrap_s, self.wrap_t] {
+            if let Wrap::ClampToBorder(color) = wrap {
+                floats(gl::TEXTURE_BORDER_COLOR, &color);
+            }
+        }
+
+        int(gl::TEXTURE_MIN_FILTER, self.min_filter_gl() as GLint);
+        int(gl::TEXTURE_MAG_FILTER, self.mag_filter_gl() as GLint);
+
+        // Written even at 1 so that turning it back off takes effect.
+        if let Some(max) = max_anisotropy() {
+            float(
+                TEXTURE_MAX_ANISOTROPY,
+                self.anisotropy.clamp(1.0, max.max(1.0)),
+            );
+        }
+    }
+}
+
+/// The driver's maximum anisotropy, or `None` when anisotropic filtering
+/// isn't supported.
+fn max_anisotropy() -> Option<f32> {
+    *MAX_ANISOTROPY
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(query_max_anisotropy)
+}
+
+/// Forgets what the last context supported. Called whenever GL is loaded
+/// for a new context.
+pub(crate) fn forget_max_anisotropy() {
+    *MAX_ANISOTROPY.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+fn query_max_anisotropy() -> Option<f32> {
+    if !anisotropy_supported() {
+        return None;
+    }
+    let mut max = 0.0;
+    unsafe {
+        gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max);
+    }
+    Some(max)
+}
+
+fn anisotropy_supported() -> bool {
+    let (mut major, mut minor, mut count) = (0, 0, 0);
+    unsafe {
+        gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+        gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+        gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+    }
+    if (major, minor) >= (4, 6) {
+        return true;
+    }
+    (0..count.max(0) as GLuint).any(|index| {
+        let name = unsafe { gl::GetStringi(gl::EXTENSIONS, index) };
+        !name.is_null()
+            && matches!(
+                unsafe { CStr::from_ptr(name as *const c_char) }.to_bytes(),
+                b"GL_EXT_texture_filter_anisotropic" | b"GL_ARB_texture_filter_anisotropic"
+            )
+    })
+}
+
+/// An owned GL sampler object, deleted on drop. While bound to a unit its
+/// state is used instead of the bound texture's.
+pub struct Sampler {
+    id: GLuint,
+}
+
+impl Sampler {
+    pub fn new(options: &SamplerOptions) -> Sampler {
+        let mut id = 0;
+        unsafe {
+            gl::GenSamplers(1, &mut id);
+        }
+        let sampler = Sampler { id };
+        sampler.set_options(options);
+        sampler
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn set_options(&self, options: &SamplerOptions) {
+        options.apply_to_sampler(self.id);
+    }
+
+    pub fn bind(&self, unit: GLuint) {
+        unsafe {
+            gl::BindSampler(unit, self.id);
+        }
+    }
+
+    /// Goes back to the texture's own sampling state on `unit`.
+    pub fn unbind(unit: GLuint) {
+        unsafe {
+            gl::BindSampler(unit, 0);
+        }
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteSamplers(1, &self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless;
+
+    fn anisotropy(sampler: &Sampler) -> f32 {
+        let mut value = 0.0;
+        unsafe { gl::GetSamplerParameterfv(sampler.id(), TEXTURE_MAX_ANISOTROPY, &mut value) };
+        value
+    }
+
+    #[test]
+    fn anisotropy_goes_back_to_one() {
+        let Some(_gl) = headless::test_context() else {
+            return;
+        };
+        if max_anisotropy().is_none_or(|max| max < 2.0) {
+            eprintln!("skipping: no anisotropic filtering");
+            return;
+        }
+
+        let sampler = Sampler::new(&SamplerOptions::new().anisotropy(2.0));
+        assert_eq!(anisotropy(&sampler), 2.0);
+        sampler.set_options(&SamplerOptions::new());
+        assert_eq!(anisotropy(&sampler), 1.0);
+    }
+
+    #[test]
+    fn max_anisotropy_is_looked_up_once_per_context() {
+        let Some(_gl) = headless::test_context() else {
+            return;
+        };
+        let max = max_anisotropy();
+        assert_eq!(*MAX_ANISOTROPY.lock().unwrap(), Some(max));
+
+        forget_max_anisotropy();
+        assert_eq!(*MAX_ANISOTROPY.lock().unwrap(), None);
+        let _sampler = Sampler::new(&SamplerOptions::new());
+        assert_eq!(*MAX_ANISOTROPY.lock().unwrap(), Some(max));
+    }
+
+    #[test]
+    fn applying_options_leaves_earlier_errors_alone() {
+        let Some(_gl) = headless::test_context() else {
+            return;
+        };
+        unsafe {
+            while gl::GetError() != gl::NO_ERROR {}
+            gl::Enable(0);
+        }
+        let _sampler = Sampler::new(&SamplerOptions::new().anisotropy(4.0));
+        assert_eq!(unsafe { gl::GetError() }, gl::INVALID_ENUM);
+    }
+}
@@ -0,0 +1,580 @@
+extern crate gl;
+use super::preprocess::{PreprocessError, Preprocessed, Preprocessor};
+use gl::types::*;
+use std::error::Error;
+use std::ffi::CString;
+use std::fmt;
+use std::ptr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+    Geometry,
+    Unknown(GLenum),
+}
+
+impl ShaderStage {
+    pub fn from_gl(ty: GLenum) -> Self {
+        match ty {
+            gl::VERTEX_SHADER => ShaderStage::Vertex,
+            gl::FRAGMENT_SHADER => ShaderStage::Fragment,
+            gl::GEOMETRY_SHADER => ShaderStage::Geometry,
+            _ => ShaderStage::Unknown(ty),
+        }
+    }
+}
+
+impl fmt::Display for ShaderStage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShaderStage::Vertex => write!(f, "vertex"),
+            ShaderStage::Fragment => write!(f, "fragment"),
+            ShaderStage::Geometry => write!(f, "geometry"),
+            ShaderStage::Unknown(ty) => write!(f, "shader type 0x{:x}", ty),
+        }
+    }
+}
+
+/// One diagnostic from a driver info log. `line` and `column` are `None`
+/// when the driver didn't report them or used a format we don't recognise.
+/// `file` names the include the line came from, or is `None` for the
+/// top-level source. `mapped` is false while `line` is still the driver's
+/// number in the preprocessed source rather than a line of `file`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub mapped: bool,
+    pub severity: Option<Severity>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ShaderError {
+    Compile {
+        stage: ShaderStage,
+        source: String,
+        log: Vec<LogEntry>,
+    },
+    Link {
+        shaders: Vec<(ShaderStage, String)>,
+        log: Vec<LogEntry>,
+    },
+}
+
+impl ShaderError {
+    pub fn log(&self) -> &[LogEntry] {
+        match self {
+            ShaderError::Compile { log, .. } | ShaderError::Link { log, .. } => log,
+        }
+    }
+}
+
+impl From<PreprocessError> for LogEntry {
+    fn from(e: PreprocessError) -> Self {
+        LogEntry {
+            file: e.location.file,
+            line: Some(e.location.line),
+            column: None,
+            mapped: true,
+            severity: Some(Severity::Error),
+            message: e.message,
+        }
+    }
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShaderError::Compile { stage, source, log } => {
+                writeln!(f, "failed to compile {} shader", stage)?;
+                write_log(f, log, Some(source))
+            }
+            ShaderError::Link { shaders, log } => {
+                let stages: Vec<String> = shaders.iter().map(|(s, _)| s.to_string()).collect();
+                writeln!(f, "failed to link program ({})", stages.join(", "))?;
+                write_log(f, log, None)
+            }
+        }
+    }
+}
+
+impl Error for ShaderError {}
+
+fn write_log(f: &mut fmt::Formatter, log: &[LogEntry], source: Option<&str>) -> fmt::Result {
+    for entry in log {
+        let file = entry
+            .file
+            .as_ref()
+            .map(|file| format!("{}:", file))
+            .unwrap_or_default();
+        write!(f, "  {}", file)?;
+        match (entry.line, entry.column) {
+            (Some(line), Some(column)) => write!(f, "{}:{}: ", line, column)?,
+            (Some(line), None) => write!(f, "{}: ", line)?,
+            _ => {}
+        }
+        if let Some(severity) = entry.severity {
+            write!(f, "{}: ", severity)?;
+        }
+        writeln!(f, "{}", entry.message)?;
+
+        // Only the top-level source is at hand, so included lines get no
+        // excerpt, and neither do lines that couldn't be traced back to it.
+        let text = entry
+            .line
+            .filter(|_| entry.mapped && entry.file.is_none())
+            .and_then(|line| source?.lines().nth((line as usize).checked_sub(1)?));
+        if let Some(text) = text {
+            writeln!(f, "    | {}", text)?;
+            if let Some(column) = entry.column {
+                writeln!(f, "    | {:>1$}", "^", column.max(1) as usize)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Splits a driver info log into entries, recognising the Mesa
+/// (`0:12(5): error: ...`), NVIDIA (`0(12) : error C0000: ...`) and
+/// AMD/Intel/Apple (`ERROR: 0:12: ...`) formats.
+pub fn parse_info_log(log: &str) -> Vec<LogEntry> {
+    log.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(parse_log_line)
+        .collect()
+}
+
+fn parse_log_line(line: &str) -> LogEntry {
+    let (prefix, rest) = take_severity(line, "ERROR:", "WARNING:");
+    let unparsed = {
+        let (severity, message) = match prefix {
+            Some(severity) => (Some(severity), rest),
+            None => take_severity(line, "error", "warning"),
+        };
+        LogEntry {
+            file: None,
+            line: None,
+            column: None,
+            mapped: false,
+            severity,
+            message: message.to_string(),
+        }
+    };
+
+    // Skip the source string index, then read `:line`, `:line(col)` or `(line)`.
+    let rest = rest.trim_start_matches(|c: char| c.is_ascii_digit());
+    let (line_no, rest) = if let Some(rest) = rest.strip_prefix(':') {
+        match take_number(rest) {
+            Some(parsed) => parsed,
+            None => return unparsed,
+        }
+    } else if let Some(rest) = rest.strip_prefix('(') {
+        match take_number(rest) {
+            Some((n, rest)) if rest.starts_with(')') => (n, &rest[1..]),
+            _ => return unparsed,
+        }
+    } else {
+        return unparsed;
+    };
+
+    let (column, rest) = match rest.strip_prefix('(').and_then(take_number) {
+        Some((n, rest)) if rest.starts_with(')') => (Some(n), &rest[1..]),
+        _ => (None, rest),
+    };
+
+    // Mesa and NVIDIA put the severity after the location instead.
+    let message = rest.trim_start().trim_start_matches(':').trim();
+    let (severity, message) = match prefix {
+        Some(severity) => (Some(severity), message),
+        None => take_severity(message, "error", "warning"),
+    };
+
+    LogEntry {
+        file: None,
+        line: Some(line_no),
+        column,
+        mapped: false,
+        severity,
+        message: if message.is_empty() {
+            line.to_string()
+        } else {
+            message.to_string()
+        },
+    }
+}
+
+// Splits a leading `error` or `warning`, spelled as given, off `s`.
+fn take_severity<'a>(s: &'a str, error: &str, warning: &str) -> (Option<Severity>, &'a str) {
+    for (word, severity) in [(error, Severity::Error), (warning, Severity::Warning)] {
+        let Some(rest) = s.strip_prefix(word) else {
+            continue;
+        };
+        // A whole word only, so a message starting `errors` stays whole.
+        if word.ends_with(':')
+            || rest.is_empty()
+            || rest.starts_with(|c: char| c == ':' || c.is_whitespace())
+        {
+            return (Some(severity), rest.trim_start_matches(':').trim_start());
+        }
+    }
+    (None, s)
+}
+
+// Points a driver log entry at the line it came from before preprocessing.
+// Lines the preprocessor generated keep the driver's line number, which at
+// least says where in the compiled source to look, but stay unmapped.
+fn locate(mut entry: LogEntry, preprocessed: &Preprocessed) -> LogEntry {
+    if let Some(location) = entry.line.and_then(|line| preprocessed.location(line)) {
+        entry.file = location.file.clone();
+        entry.line = Some(location.line);
+        entry.mapped = true;
+    }
+    entry
+}
+
+fn take_number(s: &str) -> Option<(u32, &str)> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    Some((s[..end].parse().ok()?, &s[end..]))
+}
+
+/// An owned shader object, deleted on drop.
+pub struct Shader {
+    id: GLuint,
+    stage: ShaderStage,
+}
+
+impl Shader {
+    pub fn compile(src: &str, ty: GLenum) -> Result<Shader, ShaderError> {
+        Ok(Shader::from_id(compile_shader(src, ty)?, ty))
+    }
+
+    pub fn compile_with(
+        src: &str,
+        ty: GLenum,
+        preprocessor: &Preprocessor,
+    ) -> Result<Shader, ShaderError> {
+        Ok(Shader::from_id(
+            compile_shader_with(src, ty, preprocessor)?,
+            ty,
+        ))
+    }
+
+    pub fn from_preprocessed(
+        src: &str,
+        preprocessed: &Preprocessed,
+        ty: GLenum,
+    ) -> Result<Shader, ShaderError> {
+        Ok(Shader::from_id(
+            compile_preprocessed(src, preprocessed, ty)?,
+            ty,
+        ))
+    }
+
+    fn from_id(id: GLuint, ty: GLenum) -> Shader {
+        Shader {
+            id,
+            stage: ShaderStage::from_gl(ty),
+        }
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn stage(&self) -> ShaderStage {
+        self.stage
+    }
+}
+
+impl Drop for Shader {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteShader(self.id);
+        }
+    }
+}
+
+pub fn compile_shader(src: &str, ty: GLenum) -> Result<GLuint, ShaderError> {
+    compile_shader_with(src, ty, &Preprocessor::default())
+}
+
+pub fn compile_shader_with(
+    src: &str,
+    ty: GLenum,
+    preprocessor: &Preprocessor,
+) -> Result<GLuint, ShaderError> {
+    match preprocessor.run(src) {
+        Ok(preprocessed) => compile_preprocessed(src, &preprocessed, ty),
+        Err(e) => Err(ShaderError::Compile {
+            stage: ShaderStage::from_gl(ty),
+            source: src.to_string(),
+            log: vec![e.into()],
+        }),
+    }
+}
+
+/// Compiles already preprocessed GLSL. `src` is the original source, kept for
+/// error reporting; log lines are mapped back through the line map.
+pub fn compile_preprocessed(
+    src: &str,
+    preprocessed: &Preprocessed,
+    ty: GLenum,
+) -> Result<GLuint, ShaderError> {
+    let compile_error = |log| ShaderError::Compile {
+        stage: ShaderStage::from_gl(ty),
+        source: src.to_string(),
+        log,
+    };
+
+    let c_str = match CString::new(preprocessed.source.as_bytes()) {
+        Ok(c_str) => c_str,
+        Err(e) => {
+            return Err(compile_error(vec![LogEntry {
+                file: None,
+                line: None,
+                column: None,
+                mapped: false,
+                severity: Some(Severity::Error),
+                message: e.to_string(),
+            }]))
+        }
+    };
+
+    unsafe {
+        let shader = gl::CreateShader(ty);
+        gl::ShaderSource(shader, 1, &c_str.as_ptr(), ptr::null());
+        gl::CompileShader(shader);
+
+        let mut status = gl::FALSE as GLint;
+        gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut status);
+
+        if status != (gl::TRUE as GLint) {
+            let mut len = 0;
+            gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut len);
+            let log = read_gl_string(len, |len, buf| {
+                gl::GetShaderInfoLog(shader, len, ptr::null_mut(), buf)
+            });
+            gl::DeleteShader(shader);
+
+            let log = parse_info_log(&log)
+                .into_iter()
+                .map(|entry| locate(entry, preprocessed))
+                .collect();
+            return Err(compile_error(log));
+        }
+        Ok(shader)
+    }
+}
+
+pub fn link_program(vs: GLuint, fs: GLuint) -> Result<GLuint, ShaderError> {
+    unsafe {
+        let program = gl::CreateProgram();
+        gl::AttachShader(program, vs);
+        gl::AttachShader(program, fs);
+        gl::LinkProgram(program);
+
+        let mut status = gl::FALSE as GLint;
+        gl::GetProgramiv(program, gl::LINK_STATUS, &mut status);
+
+        if status != (gl::TRUE as GLint) {
+            let mut len: GLint = 0;
+            gl::GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut len);
+            let log = read_gl_string(len, |len, buf| {
+                gl::GetProgramInfoLog(program, len, ptr::null_mut(), buf)
+            });
+            gl::DeleteProgram(program);
+            return Err(ShaderError::Link {
+                shaders: vec![shader_info(vs), shader_info(fs)],
+                log: parse_info_log(&log),
+            });
+        }
+        Ok(program)
+    }
+}
+
+unsafe fn read_gl_string(len: GLint, get: impl FnOnce(GLint, *mut GLchar)) -> String {
+    if len <= 0 {
+        return String::new();
+    }
+    let mut buf = vec![0u8; len as usize];
+    get(len, buf.as_mut_ptr() as *mut GLchar);
+    // Drop the trailing NUL included in the reported length.
+    buf.truncate((len as usize) - 1);
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+unsafe fn shader_info(shader: GLuint) -> (ShaderStage, String) {
+    let mut ty = 0;
+    gl::GetShaderiv(shader, gl::SHADER_TYPE, &mut ty);
+    let mut len = 0;
+    gl::GetShaderiv(shader, gl::SHADER_SOURCE_LENGTH, &mut len);
+    let source = read_gl_string(len, |len, buf| {
+        gl::GetShaderSource(shader, len, ptr::null_mut(), buf)
+    });
+    (ShaderStage::from_gl(ty as GLenum), source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preprocess::SourceLocation;
+
+    fn entry(
+        line: Option<u32>,
+        column: Option<u32>,
+        severity: Option<Severity>,
+        message: &str,
+    ) -> LogEntry {
+        LogEntry {
+            file: None,
+            line,
+            column,
+            mapped: false,
+            severity,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_each_vendor_log_format() {
+        use Severity::{Error, Warning};
+        let cases = [
+            // Mesa
+            (
+                "0:12(5): error: `foo' undeclared",
+                entry(Some(12), Some(5), Some(Error), "`foo' undeclared"),
+            ),
+            (
+                "0:4(1): warning: unused variable",
+                entry(Some(4), Some(1), Some(Warning), "unused variable"),
+            ),
+            // NVIDIA
+            (
+                "0(7) : error C1008: undefined variable \"foo\"",
+                entry(
+                    Some(7),
+                    None,
+                    Some(Error),
+                    "C1008: undefined variable \"foo\"",
+                ),
+            ),
+            // AMD, Intel and Apple
+            (
+                "ERROR: 0:3: 'foo' : undeclared identifier",
+                entry(Some(3), None, Some(Error), "'foo' : undeclared identifier"),
+            ),
+            (
+                "WARNING: 0:9: extension not supported",
+                entry(Some(9), None, Some(Warning), "extension not supported"),
+            ),
+            // Anything else is kept whole, without a location.
+            (
+                "error: linking with uncompiled shader",
+                entry(None, None, Some(Error), "linking with uncompiled shader"),
+            ),
+            (
+                "errors were found",
+                entry(None, None, None, "errors were found"),
+            ),
+        ];
+        for (log, expected) in cases {
+            assert_eq!(parse_info_log(log), vec![expected], "{}", log);
+        }
+    }
+
+    #[test]
+    fn skips_blank_log_lines() {
+        let log = "\n0:1(1): error: first\n\n  0:2(1): error: second  \n";
+        let lines: Vec<_> = parse_info_log(log).iter().map(|e| e.line).collect();
+        assert_eq!(lines, [Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn locates_lines_through_the_line_map() {
+        let preprocessed = Preprocessed {
+            source: String::new(),
+            line_map: vec![
+                None,
+                Some(SourceLocation {
+                    file: None,
+                    line: 1,
+                }),
+                Some(SourceLocation {
+                    file: Some("common.glsl".to_string()),
+                    line: 4,
+                }),
+            ],
+            includes: Vec::new(),
+        };
+
+        let included = locate(entry(Some(3), Some(2), None, "error"), &preprocessed);
+        assert_eq!(included.file.as_deref(), Some("common.glsl"));
+        assert_eq!((included.line, included.column), (Some(4), Some(2)));
+        assert!(included.mapped);
+
+        assert_eq!(
+            locate(entry(Some(2), None, None, "error"), &preprocessed).line,
+            Some(1)
+        );
+
+        // The generated `#version` line, and a line past the end, keep the
+        // driver's numbering but stay unmapped.
+        assert_eq!(
+            locate(entry(Some(1), None, None, "error"), &preprocessed),
+            entry(Some(1), None, None, "error")
+        );
+        let past_end = locate(entry(Some(40), None, None, "error"), &preprocessed);
+        assert_eq!(past_end.line, Some(40));
+        assert!(!past_end.mapped);
+    }
+
+    #[test]
+    fn prints_severity_and_excerpts_of_mapped_lines_only() {
+        let error = |log| ShaderError::Compile {
+            stage: ShaderStage::Fragment,
+            source: "void main() {\n    nope;\n}\n".to_string(),
+            log,
+        };
+        let mapped = LogEntry {
+            mapped: true,
+            ..entry(
+                Some(2),
+                Some(5),
+                Some(Severity::Warning),
+                "`nope' undeclared",
+            )
+        };
+        assert_eq!(
+            error(vec![mapped]).to_string(),
+            "failed to compile fragment shader\n\
+             \x20 2:5: warning: `nope' undeclared\n\
+             \x20   |     nope;\n\
+             \x20   |     ^\n"
+        );
+
+        let unmapped = entry(Some(2), None, Some(Severity::Error), "generated");
+        assert_eq!(
+            error(vec![unmapped]).to_string(),
+            "failed to compile fragment shader\n  2: error: generated\n"
+        );
+    }
+}
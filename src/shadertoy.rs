@@ -0,0 +1,228 @@
+extern crate gl;
+use crate::app::Demo;
+use crate::input::{Input, MouseButton};
+use crate::reload::{ReloadableProgram, ShaderFile};
+use crate::sampler::{SamplerOptions, Wrap};
+use crate::texture::{self, Texture2D, TextureOptions};
+use crate::vertex_array::VertexArray;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// `glrs/shadertoy.glsl`, included ahead of the user's shader.
+static SHADERTOY_GLSL: &str = "// The inputs Shadertoy gives an image shader.
+uniform vec3 iResolution;
+uniform float iTime;
+uniform float iTimeDelta;
+uniform int iFrame;
+uniform float iFrameRate;
+uniform vec4 iMouse;
+uniform vec4 iDate;
+uniform float iSampleRate;
+uniform float iChannelTime[4];
+uniform vec3 iChannelResolution[4];
+uniform sampler2D iChannel0;
+uniform sampler2D iChannel1;
+uniform sampler2D iChannel2;
+uniform sampler2D iChannel3;
+";
+
+static VS_SRC: &str = "
+void main() {
+    // One triangle covering the screen, made from the vertex index alone.
+    vec2 corner = vec2(float((gl_VertexID << 1) & 2), float(gl_VertexID & 2));
+    gl_Position = vec4(corner * 2.0 - 1.0, 0.0, 1.0);
+}
+";
+
+/// Texture units `iChannel0..3` are bound to.
+pub const CHANNELS: usize = 4;
+
+/// Runs a Shadertoy image shader: a file defining
+/// `void mainImage(out vec4 fragColor, in vec2 fragCoord)` that reads the
+/// `i*` uniforms Shadertoy provides. The file is reloaded when it changes.
+///
+/// `iTime` and `iTimeDelta` follow the runner's clock, `iDate` is in UTC,
+/// and `iMouse` follows the left button as on shadertoy.com: `xy` is where
+/// it was last dragged, `zw` where it was clicked, negative once released.
+pub struct Shadertoy {
+    program: ReloadableProgram,
+    vao: VertexArray,
+    channels: [Option<Texture2D>; CHANNELS],
+    resolution: [f32; 2],
+    time: f32,
+    step: f32,
+    frame: i32,
+    mouse: [f32; 4],
+}
+
+impl Shadertoy {
+    /// Compiles `shader` and loads an image for each channel that has one.
+    pub fn new(
+        shader: impl AsRef<Path>,
+        channels: &[Option<PathBuf>; CHANNELS],
+    ) -> Result<Shadertoy, Box<dyn Error>> {
+        let shader = shader.as_ref();
+        // Checked here because a missing include only says "cannot find".
+        let shader = fs::canonicalize(shader)
+            .map_err(|e| format!("can't read {}: {}", shader.display(), e))?;
+        let fragment = format!(
+            "#include \"glrs/shadertoy.glsl\"
+#include \"{}\"
+
+out vec4 out_color;
+
+void main() {{
+    vec4 color = vec4(0.0, 0.0, 0.0, 1.0);
+    mainImage(color, gl_FragCoord.xy);
+    // Shadertoy shows no alpha, and windows have none.
+    out_color = vec4(color.rgb, 1.0);
+}}
+",
+            shader.display()
+        );
+        // Built from source only, so nothing in shaders/ stands in for the
+        // chosen file; the file itself is watched as an include.
+        let program = ReloadableProgram::new(
+            ShaderFile::source(VS_SRC),
+            ShaderFile::source(fragment).builtin("glrs/shadertoy.glsl", SHADERTOY_GLSL),
+        )?;
+
+        // Shadertoy's defaults: repeating, mipmapped and upright.
+        let options = TextureOptions::new()
+            .flip_y(true)
+            .sampler(SamplerOptions::new().wrap(Wrap::Repeat));
+        let mut textures: [Option<Texture2D>; CHANNELS] = Default::default();
+        for (texture, path) in textures.iter_mut().zip(channels) {
+            if let Some(path) = path {
+                *texture = Some(texture::load_texture(path, &options)?);
+            }
+        }
+
+        Ok(Shadertoy {
+            program,
+            vao: VertexArray::new(),
+            channels: textures,
+            resolution: [0.0, 0.0],
+            time: 0.0,
+            step: 0.0,
+            frame: 0,
+            mouse: [0.0; 4],
+        })
+    }
+
+    fn track_mouse(&mut self, input: &Input) {
+        let cursor = input.cursor().map(|[x, y]| [x, self.resolution[1] - y]);
+        if let (true, Some([x, y])) = (input.button_down(MouseButton::Left), cursor) {
+            self.mouse[0] = x;
+            self.mouse[1] = y;
+            if input.button_pressed(MouseButton::Left) {
+                self.mouse[2] = x;
+                self.mouse[3] = y;
+            } else {
+                // Only the update with the click sees w positive.
+                self.mouse[3] = -self.mouse[3].abs();
+            }
+        }
+        if !input.button_down(MouseButton::Left) {
+            self.mouse[2] = -self.mouse[2].abs();
+            self.mouse[3] = -self.mouse[3].abs();
+        }
+    }
+}
+
+impl Demo for Shadertoy {
+    fn init(&mut self, width: u32, height: u32) {
+        self.resolution = [width as f32, height as f32];
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.resolution = [width as f32, height as f32];
+    }
+
+    fn update(&mut self, dt: f32, input: &Input) {
+        self.program.poll();
+        self.time += dt;
+        self.step = dt;
+        self.track_mouse(input);
+    }
+
+    fn render(&mut self, alpha: f32) {
+        let time = self.time + alpha * self.step;
+        let mut channel_resolution = [[0.0; 3]; CHANNELS];
+        for (unit, channel) in self.channels.iter().enumerate() {
+            if let Some(texture) = channel {
+                texture.bind(unit as u32);
+                channel_resolution[unit] = [texture.width() as f32, texture.height() as f32, 1.0];
+            }
+        }
+
+        // Shaders use what they like and the compiler drops the rest.
+        let program = &self.program;
+        program.bind();
+        program.set_if_active("iResolution", [self.resolution[0], self.resolution[1], 1.0]);
+        program.set_if_active("iTime", time);
+        program.set_if_active("iTimeDelta", self.step);
+        program.set_if_active("iFrame", self.frame);
+        program.set_if_active("iFrameRate", 1.0 / self.step.max(f32::EPSILON));
+        program.set_if_active("iMouse", self.mouse);
+        program.set_if_active("iDate", date(SystemTime::now()));
+        program.set_if_active("iSampleRate", 44100.0);
+        program.set_if_active("iChannelTime[0]", &[time; CHANNELS][..]);
+        program.set_if_active("iChannelResolution[0]", &channel_resolution[..]);
+        for unit in 0..CHANNELS {
+            program.set_if_active(&format!("iChannel{}", unit), unit as i32);
+        }
+
+        self.vao.bind();
+        unsafe {
+            gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        }
+        self.frame += 1;
+    }
+}
+
+// Shadertoy's `iDate`: year, month from 0, day of the month, and seconds
+// since midnight.
+fn date(now: SystemTime) -> [f32; 4] {
+    let since_epoch = now.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs();
+    let days = (seconds / 86_400) as i64;
+    let (year, month, day) = civil_from_days(days);
+    let of_day = (seconds % 86_400) as f32 + since_epoch.subsec_millis() as f32 / 1000.0;
+    [year as f32, (month - 1) as f32, day as f32, of_day]
+}
+
+// Year, month and day of the Gregorian calendar `days` after 1970-01-01,
+// after Howard Hinnant's `civil_from_days`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn dates_follow_the_gregorian_calendar() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+
+        // 2024-03-01 12:00:30 UTC.
+        let noon = UNIX_EPOCH + Duration::from_secs(1_709_294_430);
+        assert_eq!(date(noon), [2024.0, 2.0, 1.0, 43_230.0]);
+    }
+}
@@ -1,149 +1,383 @@
 extern crate gl;
-use super::utils;
+use super::sampler::SamplerOptions;
 use gl::types::*;
-use glutin::dpi::PhysicalSize;
-use glutin::event::{Event, WindowEvent};
-use glutin::event_loop::{ControlFlow, EventLoop};
-use glutin::window::WindowBuilder;
-use glutin::{ContextBuilder, GlProfile};
-use std::ffi::CString;
+use stb_image::image::{load, LoadResult};
+use std::borrow::Cow;
+use std::error::Error;
+use std::ffi::c_void;
+use std::fmt;
 use std::mem;
+use std::path::{Path, PathBuf};
 use std::ptr;
-use std::time::Instant;
 
-static VS_SRC: &str = "
-#version 330
-layout(location=0) in vec2 a_position;
-out vec2 v_texcoord;
+#[derive(Debug)]
+pub enum TextureError {
+    Load { path: PathBuf, message: String },
+    UnsupportedChannels { path: PathBuf, channels: usize },
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextureError::Load { path, message } => {
+                write!(f, "failed to load {}: {}", path.display(), message)
+            }
+            TextureError::UnsupportedChannels { path, channels } => write!(
+                f,
+                "failed to load {}: unsupported channel count {}",
+                path.display(),
+                channels
+            ),
+        }
+    }
+}
+
+impl Error for TextureError {}
 
-void main() {
-    gl_Position = vec4(a_position, 0.0, 1.0);
-    v_texcoord = -0.5 + a_position + 1.0;
+/// Pixel layout of a texture, both in memory and on the GPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFormat {
+    R8,
+    Rg8,
+    Rgb8,
+    Rgba8,
+    R32F,
+    Rg32F,
+    Rgb32F,
+    Rgba32F,
 }
-";
 
-static FS_SRC: &str = "
-#version 330
-uniform sampler2D u_sampler;
-uniform vec2 u_resolution;
-uniform float u_time;
+impl TextureFormat {
+    /// The format for `channels` interleaved 8-bit or 32-bit float channels.
+    pub fn from_channels(channels: usize, float: bool) -> Option<TextureFormat> {
+        use TextureFormat::*;
+        match (channels, float) {
+            (1, false) => Some(R8),
+            (2, false) => Some(Rg8),
+            (3, false) => Some(Rgb8),
+            (4, false) => Some(Rgba8),
+            (1, true) => Some(R32F),
+            (2, true) => Some(Rg32F),
+            (3, true) => Some(Rgb32F),
+            (4, true) => Some(Rgba32F),
+            _ => None,
+        }
+    }
+
+    pub fn channels(self) -> usize {
+        use TextureFormat::*;
+        match self {
+            R8 | R32F => 1,
+            Rg8 | Rg32F => 2,
+            Rgb8 | Rgb32F => 3,
+            Rgba8 | Rgba32F => 4,
+        }
+    }
 
-in vec2 v_texcoord;
-out vec4 out_color;
+    pub fn is_float(self) -> bool {
+        use TextureFormat::*;
+        matches!(self, R32F | Rg32F | Rgb32F | Rgba32F)
+    }
+
+    /// Size of one pixel in bytes.
+    pub fn pixel_size(self) -> usize {
+        let channel = if self.is_float() {
+            mem::size_of::<f32>()
+        } else {
+            1
+        };
+        self.channels() * channel
+    }
 
-void main() {
-    vec2 uv = gl_FragCoord.xy / u_resolution;
-    vec2 direction = vec2(uv.xy - vec2(0.5, 0.5));
-    uv *= length(direction);
-    uv.x *= cos(u_time * 0.08);
-    uv.y += sin(u_time * 0.03);
+    /// The GL internal format. `srgb` only affects 8-bit RGB and RGBA, the
+    /// formats color images are stored in.
+    pub fn internal_format(self, srgb: bool) -> GLenum {
+        use TextureFormat::*;
+        match self {
+            Rgb8 if srgb => gl::SRGB8,
+            Rgba8 if srgb => gl::SRGB8_ALPHA8,
+            R8 => gl::R8,
+            Rg8 => gl::RG8,
+            Rgb8 => gl::RGB8,
+            Rgba8 => gl::RGBA8,
+            R32F => gl::R32F,
+            Rg32F => gl::RG32F,
+            Rgb32F => gl::RGB32F,
+            Rgba32F => gl::RGBA32F,
+        }
+    }
 
-    out_color = texture(u_sampler, uv);
+    /// The `format` and `type` arguments of `glTexImage2D`.
+    pub fn pixel_format(self) -> (GLenum, GLenum) {
+        let format = match self.channels() {
+            1 => gl::RED,
+            2 => gl::RG,
+            3 => gl::RGB,
+            _ => gl::RGBA,
+        };
+        let ty = if self.is_float() {
+            gl::FLOAT
+        } else {
+            gl::UNSIGNED_BYTE
+        };
+        (format, ty)
+    }
+}
+
+/// How a texture is stored and sampled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureOptions {
+    pub sampler: SamplerOptions,
+    /// Build the full mip chain after uploading.
+    pub mipmaps: bool,
+    /// Treat 8-bit color data as sRGB so sampling returns linear values.
+    pub srgb: bool,
+    /// Have `load_texture` put an image's top row at t = 1 rather than 0,
+    /// so it samples upright.
+    pub flip_y: bool,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        TextureOptions {
+            sampler: SamplerOptions::default(),
+            mipmaps: true,
+            srgb: false,
+            flip_y: false,
+        }
+    }
 }
-";
-
-pub fn main() {
-    let width = 1024;
-    let height = 768;
-
-    let el = EventLoop::new();
-    let wb = WindowBuilder::new()
-        .with_inner_size(PhysicalSize::new(width, height))
-        .with_resizable(false)
-        .with_title(" ");
-
-    let context = ContextBuilder::new()
-        .with_gl_profile(GlProfile::Core)
-        .with_vsync(true)
-        .build_windowed(wb, &el)
-        .unwrap();
-    let context = unsafe { context.make_current().unwrap() };
-
-    gl::load_with(|symbol| context.get_proc_address(symbol));
-
-    utils::center_window(&context);
-
-    let vs = utils::compile_shader(VS_SRC, gl::VERTEX_SHADER);
-    let fs = utils::compile_shader(FS_SRC, gl::FRAGMENT_SHADER);
-    let program = utils::link_program(vs, fs);
-
-    let vertices: Vec<f32> = vec![
-        0.5, -0.5, -0.5, 0.5, -0.5, -0.5, 0.5, 0.5, -0.5, 0.5, 0.5, -0.5,
-    ];
-
-    let mut vao = 0;
-    let mut vertex_vbo = 0;
-    let mut texture = 0;
-    let u_resolution;
-    let u_time;
-
-    #[allow(temporary_cstring_as_ptr)]
-    unsafe {
-        gl::UseProgram(program);
-
-        gl::GenVertexArrays(1, &mut vao);
-        gl::BindVertexArray(vao);
-
-        gl::GenBuffers(1, &mut vertex_vbo);
-        gl::BindBuffer(gl::ARRAY_BUFFER, vertex_vbo);
-        gl::BufferData(
-            gl::ARRAY_BUFFER,
-            (vertices.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
-            vertices.as_ptr() as *const GLvoid,
-            gl::STATIC_DRAW,
-        );
 
-        let a_position =
-            gl::GetAttribLocation(program, CString::new("a_position").unwrap().as_ptr());
-        gl::EnableVertexAttribArray(a_position as GLuint);
-        gl::VertexAttribPointer(a_position as u32, 2, gl::FLOAT, gl::FALSE, 0, ptr::null());
+impl TextureOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sampler(mut self, sampler: SamplerOptions) -> Self {
+        self.sampler = sampler;
+        self
+    }
+
+    pub fn mipmaps(mut self, mipmaps: bool) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
 
-        let u_sampler =
-            gl::GetUniformLocation(program, CString::new("u_sampler").unwrap().as_ptr());
-        utils::load_texture("bricks.png", &mut texture);
-        gl::BindTexture(gl::TEXTURE_2D, texture);
-        gl::Uniform1i(u_sampler, 0);
+    pub fn srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
 
-        u_resolution =
-            gl::GetUniformLocation(program, CString::new("u_resolution").unwrap().as_ptr());
+    pub fn flip_y(mut self, flip_y: bool) -> Self {
+        self.flip_y = flip_y;
+        self
+    }
+}
 
-        u_time = gl::GetUniformLocation(program, CString::new("u_time").unwrap().as_ptr());
+/// An owned 2D texture object, deleted on drop.
+pub struct Texture2D {
+    id: GLuint,
+    width: i32,
+    height: i32,
+    format: TextureFormat,
+}
 
-        gl::Uniform2f(u_resolution, width as f32, height as f32);
+impl Texture2D {
+    pub fn new() -> Texture2D {
+        let mut id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut id);
+        }
+        Texture2D {
+            id,
+            width: 0,
+            height: 0,
+            format: TextureFormat::Rgba8,
+        }
     }
 
-    let start_time = Instant::now();
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
 
-    el.run(move |event, _, control_flow| {
-        context.window().request_redraw();
+    pub fn width(&self) -> i32 {
+        self.width
+    }
 
-        match event {
-            Event::LoopDestroyed => {}
-            Event::WindowEvent { event, .. } => {
-                if event == WindowEvent::CloseRequested {
-                    unsafe {
-                        gl::DeleteProgram(program);
-                        gl::DeleteShader(vs);
-                        gl::DeleteShader(fs);
-                        gl::DeleteBuffers(1, &vertex_vbo);
-                        gl::DeleteVertexArrays(1, &vao);
-                    }
-                    *control_flow = ControlFlow::Exit
-                }
-            }
-            Event::RedrawRequested(_) => {
-                context.swap_buffers().unwrap();
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    /// Binds the texture to texture unit `unit`.
+    pub fn bind(&self, unit: GLuint) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+        }
+    }
+
+    /// Replaces the texture's storage with tightly packed rows of `data`,
+    /// which must hold `width * height` pixels of `format`. Binds the
+    /// texture to unit 0.
+    pub fn upload<T: Copy>(
+        &mut self,
+        width: i32,
+        height: i32,
+        format: TextureFormat,
+        data: &[T],
+        options: &TextureOptions,
+    ) {
+        let row = width as usize * format.pixel_size();
+        assert_eq!(
+            mem::size_of_val(data),
+            row * height as usize,
+            "pixel data doesn't match a {}x{} {:?} texture",
+            width,
+            height,
+            format
+        );
+
+        self.store(
+            width,
+            height,
+            format,
+            data.as_ptr() as *const c_void,
+            options,
+        );
+    }
+
+    /// Replaces the texture's storage with `width * height` pixels of
+    /// undefined contents, e.g. to render into. Binds the texture to unit 0.
+    pub fn allocate(
+        &mut self,
+        width: i32,
+        height: i32,
+        format: TextureFormat,
+        options: &TextureOptions,
+    ) {
+        self.store(width, height, format, ptr::null(), options);
+    }
+
+    fn store(
+        &mut self,
+        width: i32,
+        height: i32,
+        format: TextureFormat,
+        data: *const c_void,
+        options: &TextureOptions,
+    ) {
+        let row = width as usize * format.pixel_size();
+
+        self.width = width;
+        self.height = height;
+        self.format = format;
+        self.bind(0);
+
+        let (pixel_format, ty) = format.pixel_format();
+        unsafe {
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, unpack_alignment(row));
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                format.internal_format(options.srgb) as GLint,
+                width,
+                height,
+                0,
+                pixel_format,
+                ty,
+                data,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+
+            if options.mipmaps {
+                gl::GenerateMipmap(gl::TEXTURE_2D);
             }
-            Event::MainEventsCleared => unsafe {
-                let elapsed_duration = Instant::now().duration_since(start_time).as_secs_f32();
+        }
 
-                gl::Uniform1f(u_time, elapsed_duration);
+        let mut sampler = options.sampler;
+        if !options.mipmaps {
+            // A mipmapped min filter without the levels leaves the texture
+            // incomplete, and it samples as black.
+            sampler.mipmap_filter = None;
+        }
+        self.set_sampler(&sampler);
+    }
 
-                gl::ClearColor(1.0, 1.0, 1.0, 1.0);
-                gl::Clear(gl::COLOR_BUFFER_BIT);
-                gl::DrawArrays(gl::TRIANGLES, 0, 6);
-            },
-            _ => (),
+    /// Replaces the texture's own sampling state. Binds the texture to
+    /// unit 0.
+    pub fn set_sampler(&self, sampler: &SamplerOptions) {
+        self.bind(0);
+        sampler.apply_to_texture(gl::TEXTURE_2D);
+    }
+}
+
+/// The largest alignment GL accepts that rows of `row` bytes satisfy.
+fn unpack_alignment(row: usize) -> GLint {
+    [8, 4, 2]
+        .into_iter()
+        .find(|a| row.is_multiple_of(*a))
+        .unwrap_or(1) as GLint
+}
+
+impl Default for Texture2D {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Texture2D {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
         }
-    });
+    }
+}
+
+/// Loads an image file into a texture, keeping its channel count and
+/// whether it holds 8-bit or float (e.g. HDR) data.
+pub fn load_texture(
+    path: impl AsRef<Path>,
+    options: &TextureOptions,
+) -> Result<Texture2D, TextureError> {
+    let path = path.as_ref();
+    let unsupported = |channels| TextureError::UnsupportedChannels {
+        path: path.to_path_buf(),
+        channels,
+    };
+    let mut texture = Texture2D::new();
+
+    match load(path) {
+        LoadResult::Error(message) => {
+            return Err(TextureError::Load {
+                path: path.to_path_buf(),
+                message,
+            })
+        }
+        LoadResult::ImageU8(i) => {
+            let format =
+                TextureFormat::from_channels(i.depth, false).ok_or_else(|| unsupported(i.depth))?;
+            let data = rows(&i.data, i.width * i.depth, options.flip_y);
+            texture.upload(i.width as i32, i.height as i32, format, &data, options);
+        }
+        LoadResult::ImageF32(i) => {
+            let format =
+                TextureFormat::from_channels(i.depth, true).ok_or_else(|| unsupported(i.depth))?;
+            let data = rows(&i.data, i.width * i.depth, options.flip_y);
+            texture.upload(i.width as i32, i.height as i32, format, &data, options);
+        }
+    }
+
+    Ok(texture)
+}
+
+// Image rows in the order to upload them, bottom row first when flipping.
+fn rows<T: Copy>(data: &[T], row: usize, flip: bool) -> Cow<'_, [T]> {
+    if !flip || row == 0 {
+        return Cow::Borrowed(data);
+    }
+    Cow::Owned(data.chunks_exact(row).rev().flatten().copied().collect())
 }
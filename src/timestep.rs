@@ -0,0 +1,88 @@
+/// Turns variable frame times into a whole number of fixed-size updates,
+/// carrying the remainder over to the next frame.
+pub struct FixedTimestep {
+    step: f32,
+    accumulator: f32,
+    max_steps: u32,
+}
+
+/// Updates run per frame before the timestep gives up and drops time.
+pub const DEFAULT_MAX_STEPS: u32 = 8;
+
+impl FixedTimestep {
+    /// Steps `rate` times per second.
+    pub fn new(rate: f32) -> FixedTimestep {
+        assert!(rate > 0.0, "update rate must be positive");
+        FixedTimestep {
+            step: 1.0 / rate,
+            accumulator: 0.0,
+            max_steps: DEFAULT_MAX_STEPS,
+        }
+    }
+
+    /// Caps the updates per frame. When a frame takes longer than
+    /// `max_steps` updates cover (a breakpoint, a dragged window, a machine
+    /// too slow for the rate), the excess is dropped and the simulation runs
+    /// slow instead of falling further behind every frame.
+    pub fn max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps.max(1);
+        self
+    }
+
+    /// Seconds per update.
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    /// Adds a frame's worth of time and returns how many updates to run.
+    pub fn advance(&mut self, dt: f32) -> u32 {
+        self.accumulator += dt;
+
+        let mut steps = 0;
+        while self.accumulator >= self.step {
+            if steps == self.max_steps {
+                self.accumulator %= self.step;
+                break;
+            }
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+        steps
+    }
+
+    /// How far between the last update and the next the current frame is,
+    /// from 0 to 1, for interpolating what gets drawn.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn carries_the_remainder() {
+        let mut timestep = FixedTimestep::new(10.0);
+        assert_eq!(timestep.advance(0.25), 2);
+        assert!((timestep.alpha() - 0.5).abs() < 1e-4);
+        assert_eq!(timestep.advance(0.05), 1);
+        assert!(timestep.alpha() < 1e-4);
+    }
+
+    #[test]
+    fn matching_frame_time_steps_once() {
+        let mut timestep = FixedTimestep::new(60.0);
+        for _ in 0..600 {
+            assert_eq!(timestep.advance(1.0 / 60.0), 1);
+        }
+    }
+
+    #[test]
+    fn long_frames_drop_time() {
+        let mut timestep = FixedTimestep::new(60.0).max_steps(4);
+        assert_eq!(timestep.advance(2.0), 4);
+        assert!(timestep.alpha() < 1.0);
+        assert_eq!(timestep.advance(0.0), 0);
+    }
+}
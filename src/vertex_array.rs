@@ -0,0 +1,390 @@
+extern crate gl;
+use super::buffer::Buffer;
+use super::program::Program;
+use gl::types::*;
+use std::mem;
+
+pub use glrs_derive::VertexLayout;
+
+/// One attribute of a vertex struct, as produced by `#[derive(VertexLayout)]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VertexAttribute {
+    pub name: &'static str,
+    pub location: GLuint,
+    pub components: GLint,
+    pub ty: GLenum,
+    /// Passed to the shader as ints rather than converted to floats.
+    pub integer: bool,
+    pub normalized: bool,
+    /// Byte offset of the field within the struct.
+    pub offset: usize,
+}
+
+/// A `#[repr(C)]` struct that can be read straight out of a vertex buffer.
+/// Derive it rather than implementing it by hand.
+///
+/// ```
+/// use glrs::vertex_array::VertexLayout;
+///
+/// #[repr(C)]
+/// #[derive(Clone, Copy, VertexLayout)]
+/// struct Vertex {
+///     position: [f32; 2],
+///     #[vertex(normalized)]
+///     color: [u8; 4],
+/// }
+/// ```
+///
+/// Without `#[repr(C)]` the field order isn't fixed, so the derive refuses:
+///
+/// ```compile_fail
+/// use glrs::vertex_array::VertexLayout;
+///
+/// #[derive(Clone, Copy, VertexLayout)]
+/// struct Vertex {
+///     position: [f32; 2],
+/// }
+/// ```
+pub trait VertexLayout: Copy {
+    fn attributes() -> Vec<VertexAttribute>;
+}
+
+/// A field type that maps to a single vertex attribute.
+pub trait AttributeType {
+    const COMPONENTS: GLint;
+    const GL_TYPE: GLenum;
+    const INTEGER: bool;
+}
+
+macro_rules! attribute_type {
+    (@array $t:ty, $gl:expr, $integer:expr, $($n:literal)*) => {
+        $(
+            impl AttributeType for [$t; $n] {
+                const COMPONENTS: GLint = $n;
+                const GL_TYPE: GLenum = $gl;
+                const INTEGER: bool = $integer;
+            }
+        )*
+    };
+    ($t:ty, $gl:expr, $integer:expr) => {
+        impl AttributeType for $t {
+            const COMPONENTS: GLint = 1;
+            const GL_TYPE: GLenum = $gl;
+            const INTEGER: bool = $integer;
+        }
+
+        attribute_type!(@array $t, $gl, $integer, 1 2 3 4);
+    };
+}
+
+attribute_type!(f32, gl::FLOAT, false);
+attribute_type!(i32, gl::INT, true);
+attribute_type!(u32, gl::UNSIGNED_INT, true);
+attribute_type!(i16, gl::SHORT, true);
+attribute_type!(u16, gl::UNSIGNED_SHORT, true);
+attribute_type!(i8, gl::BYTE, true);
+attribute_type!(u8, gl::UNSIGNED_BYTE, true);
+
+/// An owned vertex array object, deleted on drop.
+pub struct VertexArray {
+    id: GLuint,
+}
+
+impl VertexArray {
+    pub fn new() -> VertexArray {
+        let mut id = 0;
+        unsafe {
+            gl::GenVertexArrays(1, &mut id);
+        }
+        VertexArray { id }
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindVertexArray(self.id);
+        }
+    }
+
+    /// Points attribute `index` at float data in `buffer`. `stride` and
+    /// `offset` are in bytes; a `divisor` above zero makes it per-instance.
+    pub fn attrib_f32(
+        &self,
+        buffer: &Buffer,
+        index: GLuint,
+        components: GLint,
+        stride: usize,
+        offset: usize,
+        divisor: GLuint,
+    ) {
+        self.bind();
+        buffer.bind();
+        unsafe {
+            gl::EnableVertexAttribArray(index);
+            gl::VertexAttribPointer(
+                index,
+                components,
+                gl::FLOAT,
+                gl::FALSE,
+                stride as GLsizei,
+                offset as *const GLvoid,
+            );
+            gl::VertexAttribDivisor(index, divisor);
+        }
+    }
+
+    /// Points every attribute of `T` at `buffer`, which holds an array of
+    /// `T`. A `divisor` above zero makes them per-instance.
+    pub fn bind_layout<T: VertexLayout>(&self, buffer: &Buffer, divisor: GLuint) {
+        for attribute in T::attributes() {
+            self.attrib::<T>(buffer, attribute.location, &attribute, divisor);
+        }
+    }
+
+    /// Like `bind_layout`, but looks each attribute up in `program` by field
+    /// name instead of using the declared locations. For shaders without
+    /// `layout(location = N)`; fields the program doesn't use are skipped.
+    pub fn bind_layout_to<T: VertexLayout>(
+        &self,
+        buffer: &Buffer,
+        program: &Program,
+        divisor: GLuint,
+    ) {
+        for attribute in T::attributes() {
+            if let Some(location) = program.attribute_location(attribute.name) {
+                self.attrib::<T>(buffer, location, &attribute, divisor);
+            }
+        }
+    }
+
+    fn attrib<T: VertexLayout>(
+        &self,
+        buffer: &Buffer,
+        index: GLuint,
+        attribute: &VertexAttribute,
+        divisor: GLuint,
+    ) {
+        let stride = mem::size_of::<T>() as GLsizei;
+        let offset = attribute.offset as *const GLvoid;
+
+        self.bind();
+        buffer.bind();
+        unsafe {
+            gl::EnableVertexAttribArray(index);
+            if attribute.integer {
+                gl::VertexAttribIPointer(index, attribute.components, attribute.ty, stride, offset);
+            } else {
+                let normalized = if attribute.normalized {
+                    gl::TRUE
+                } else {
+                    gl::FALSE
+                };
+                gl::VertexAttribPointer(
+                    index,
+                    attribute.components,
+                    attribute.ty,
+                    normalized,
+                    stride,
+                    offset,
+                );
+            }
+            gl::VertexAttribDivisor(index, divisor);
+        }
+    }
+}
+
+impl Default for VertexArray {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for VertexArray {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Usage;
+    use crate::headless;
+    use crate::shader::Shader;
+
+    #[repr(C)]
+    #[derive(Clone, Copy, VertexLayout)]
+    struct Vertex {
+        position: [f32; 3],
+        #[vertex(skip)]
+        _padding: f32,
+        #[vertex(normalized)]
+        color: [u8; 4],
+        #[vertex(location = 5)]
+        id: u32,
+        weight: f32,
+    }
+
+    fn attribute(name: &'static str) -> VertexAttribute {
+        *Vertex::attributes()
+            .iter()
+            .find(|attribute| attribute.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn derive_lays_out_fields_in_order() {
+        let names: Vec<_> = Vertex::attributes().iter().map(|a| a.name).collect();
+        assert_eq!(names, ["position", "color", "id", "weight"]);
+
+        assert_eq!(
+            attribute("position"),
+            VertexAttribute {
+                name: "position",
+                location: 0,
+                components: 3,
+                ty: gl::FLOAT,
+                integer: false,
+                normalized: false,
+                offset: 0,
+            }
+        );
+        // Skipped fields take no location but keep their bytes.
+        assert_eq!(
+            attribute("color"),
+            VertexAttribute {
+                name: "color",
+                location: 1,
+                components: 4,
+                ty: gl::UNSIGNED_BYTE,
+                integer: false,
+                normalized: true,
+                offset: 16,
+            }
+        );
+        assert_eq!(
+            attribute("id"),
+            VertexAttribute {
+                name: "id",
+                location: 5,
+                components: 1,
+                ty: gl::UNSIGNED_INT,
+                integer: true,
+                normalized: false,
+                offset: 20,
+            }
+        );
+        // Locations carry on from an explicit one.
+        assert_eq!(attribute("weight").location, 6);
+        assert_eq!(attribute("weight").offset, 24);
+        assert_eq!(mem::size_of::<Vertex>(), 28);
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, VertexLayout)]
+    struct Tagged<T: AttributeType + Copy> {
+        tag: u8,
+        value: T,
+    }
+
+    #[test]
+    fn derive_handles_generic_structs() {
+        let offsets = |attributes: Vec<VertexAttribute>| -> Vec<_> {
+            attributes.iter().map(|a| (a.ty, a.offset)).collect()
+        };
+        assert_eq!(
+            offsets(Tagged::<f32>::attributes()),
+            [(gl::UNSIGNED_BYTE, 0), (gl::FLOAT, 4)]
+        );
+        assert_eq!(
+            offsets(Tagged::<[u8; 2]>::attributes()),
+            [(gl::UNSIGNED_BYTE, 0), (gl::UNSIGNED_BYTE, 1)]
+        );
+    }
+
+    #[test]
+    fn bind_layout_to_uses_the_program_locations() {
+        let Some(_gl) = headless::test_context() else {
+            return;
+        };
+
+        let vs = Shader::compile(
+            "#version 330 core
+in float weight;
+in vec4 color;
+in vec3 position;
+void main() {
+    gl_Position = vec4(position * weight, 1.0) + color;
+}",
+            gl::VERTEX_SHADER,
+        )
+        .unwrap();
+        let fs = Shader::compile(
+            "#version 330 core
+out vec4 out_color;
+void main() {
+    out_color = vec4(1.0);
+}",
+            gl::FRAGMENT_SHADER,
+        )
+        .unwrap();
+        let program = Program::link(&vs, &fs).unwrap();
+
+        let vao = VertexArray::new();
+        let mut buffer = Buffer::array();
+        buffer.upload(
+            &[Vertex {
+                position: [0.0; 3],
+                _padding: 0.0,
+                color: [0; 4],
+                id: 0,
+                weight: 1.0,
+            }],
+            Usage::Static,
+        );
+        vao.bind_layout_to::<Vertex>(&buffer, &program, 0);
+
+        let get = |index: GLuint, name: GLenum| {
+            let mut value = 0;
+            unsafe { gl::GetVertexAttribiv(index, name, &mut value) };
+            value
+        };
+        for name in ["position", "color", "weight"] {
+            let expected = attribute(name);
+            let index = program.attribute_location(name).unwrap();
+            assert_eq!(get(index, gl::VERTEX_ATTRIB_ARRAY_ENABLED), 1, "{}", name);
+            assert_eq!(
+                get(index, gl::VERTEX_ATTRIB_ARRAY_SIZE),
+                expected.components
+            );
+            assert_eq!(
+                get(index, gl::VERTEX_ATTRIB_ARRAY_TYPE) as GLenum,
+                expected.ty
+            );
+            assert_eq!(
+                get(index, gl::VERTEX_ATTRIB_ARRAY_NORMALIZED) == 1,
+                expected.normalized
+            );
+            assert_eq!(get(index, gl::VERTEX_ATTRIB_ARRAY_STRIDE), 28);
+
+            let mut offset: *mut GLvoid = std::ptr::null_mut();
+            // The bindings declare the out parameter const, but GL writes it.
+            let out = &mut offset as *mut *mut GLvoid as *const *mut GLvoid;
+            unsafe { gl::GetVertexAttribPointerv(index, gl::VERTEX_ATTRIB_ARRAY_POINTER, out) };
+            assert_eq!(offset as usize, expected.offset, "{}", name);
+        }
+
+        // `id` isn't in the program, so nothing else is enabled.
+        let mut max = 0;
+        unsafe { gl::GetIntegerv(gl::MAX_VERTEX_ATTRIBS, &mut max) };
+        let enabled = (0..max as GLuint)
+            .filter(|&index| get(index, gl::VERTEX_ATTRIB_ARRAY_ENABLED) == 1)
+            .count();
+        assert_eq!(enabled, 3);
+    }
+}
@@ -0,0 +1,322 @@
+use glutin::dpi::{PhysicalPosition, PhysicalSize, Size};
+use glutin::monitor::{MonitorHandle, VideoMode};
+use glutin::window::{Fullscreen, Window};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// How the window covers the screen. Monitors are numbered in the order
+/// `glrs monitors` lists them; `None` means the one the window is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowMode {
+    #[default]
+    Windowed,
+    /// A window without decorations covering one monitor, leaving its video
+    /// mode alone.
+    Borderless { monitor: Option<usize> },
+    /// Takes over one monitor, switching it to `video_mode`, or to its
+    /// largest and fastest mode without one.
+    Exclusive {
+        monitor: Option<usize>,
+        video_mode: Option<VideoModeRequest>,
+    },
+    /// A window without decorations covering the bounding box of every
+    /// monitor.
+    Span,
+}
+
+impl WindowMode {
+    pub fn is_windowed(&self) -> bool {
+        *self == WindowMode::Windowed
+    }
+}
+
+/// Parses `windowed`, `borderless`, `fullscreen` or `span`, with no
+/// monitor or video mode chosen.
+impl FromStr for WindowMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "windowed" => Ok(WindowMode::Windowed),
+            "borderless" => Ok(WindowMode::Borderless { monitor: None }),
+            "fullscreen" => Ok(WindowMode::Exclusive {
+                monitor: None,
+                video_mode: None,
+            }),
+            "span" => Ok(WindowMode::Span),
+            _ => Err(format!(
+                "invalid window mode `{}`, expected windowed, borderless, fullscreen or span",
+                s
+            )),
+        }
+    }
+}
+
+/// A video mode to look for, such as `1920x1080@60`. A missing refresh
+/// rate takes the fastest the monitor has at that size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VideoModeRequest {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate: Option<u16>,
+}
+
+impl FromStr for VideoModeRequest {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid video mode `{}`, expected WIDTHxHEIGHT[@HZ]", s);
+        let (size, refresh_rate) = match s.split_once('@') {
+            Some((size, hz)) => (size, Some(hz.parse().map_err(|_| invalid())?)),
+            None => (s, None),
+        };
+        let (width, height) = size
+            .split_once('x')
+            .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+            .ok_or_else(invalid)?;
+        Ok(VideoModeRequest {
+            width,
+            height,
+            refresh_rate,
+        })
+    }
+}
+
+impl fmt::Display for VideoModeRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)?;
+        if let Some(hz) = self.refresh_rate {
+            write!(f, "@{}", hz)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum WindowModeError {
+    /// The platform reported no monitors at all.
+    NoMonitors,
+    NoSuchMonitor {
+        index: usize,
+        count: usize,
+    },
+    /// The monitor has no video mode matching the request, or none at all
+    /// without one.
+    NoVideoMode {
+        monitor: String,
+        request: Option<VideoModeRequest>,
+    },
+}
+
+impl fmt::Display for WindowModeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WindowModeError::NoMonitors => write!(f, "no monitors found"),
+            WindowModeError::NoSuchMonitor { index, count } => write!(
+                f,
+                "no monitor {}, there are {} (see `glrs monitors`)",
+                index, count
+            ),
+            WindowModeError::NoVideoMode {
+                monitor,
+                request: Some(request),
+            } => write!(f, "{} has no {} video mode", monitor, request),
+            WindowModeError::NoVideoMode {
+                monitor,
+                request: None,
+            } => write!(f, "{} lists no video modes", monitor),
+        }
+    }
+}
+
+impl Error for WindowModeError {}
+
+/// The monitor's name, or a placeholder when the platform doesn't give one.
+pub fn monitor_name(monitor: &MonitorHandle) -> String {
+    monitor
+        .name()
+        .unwrap_or_else(|| "unnamed monitor".to_string())
+}
+
+// The monitor the window is on. Some platforms can't say which that is, so
+// this falls back to the primary monitor, then to any monitor.
+fn current_monitor(window: &Window) -> Option<MonitorHandle> {
+    window
+        .current_monitor()
+        .or_else(|| window.primary_monitor())
+        .or_else(|| window.available_monitors().next())
+}
+
+/// Centres the window on the monitor it is on, or leaves it where the system
+/// put it when there is no monitor information.
+pub fn center_window(window: &Window) {
+    let monitor = match current_monitor(window) {
+        Some(monitor) => monitor,
+        None => return,
+    };
+
+    let origin = monitor.position();
+    let area = monitor.size();
+    let size = window.outer_size();
+    let offset = |area: u32, size: u32| (area as i32 - size as i32).max(0) / 2;
+    window.set_outer_position(PhysicalPosition::new(
+        origin.x + offset(area.width, size.width),
+        origin.y + offset(area.height, size.height),
+    ));
+}
+
+/// Switches the window to `mode`. Going back to `Windowed` restores
+/// decorations and `windowed_size` and centres the window again.
+pub fn set_mode(
+    window: &Window,
+    mode: WindowMode,
+    windowed_size: impl Into<Size>,
+) -> Result<(), WindowModeError> {
+    match mode {
+        WindowMode::Windowed => {
+            window.set_fullscreen(None);
+            window.set_decorations(true);
+            window.set_inner_size(windowed_size);
+            center_window(window);
+        }
+        WindowMode::Borderless { monitor } => {
+            let monitor = find_monitor(window, monitor)?;
+            window.set_fullscreen(Some(Fullscreen::Borderless(Some(monitor))));
+        }
+        WindowMode::Exclusive {
+            monitor,
+            video_mode,
+        } => {
+            let monitor = find_monitor(window, monitor)?;
+            let video_mode = pick_video_mode(&monitor, video_mode)?;
+            window.set_fullscreen(Some(Fullscreen::Exclusive(video_mode)));
+        }
+        WindowMode::Span => {
+            let (origin, size) =
+                monitor_bounds(window.available_monitors()).ok_or(WindowModeError::NoMonitors)?;
+            window.set_fullscreen(None);
+            window.set_decorations(false);
+            window.set_outer_position(origin);
+            window.set_inner_size(size);
+        }
+    }
+    Ok(())
+}
+
+fn find_monitor(window: &Window, index: Option<usize>) -> Result<MonitorHandle, WindowModeError> {
+    let index = match index {
+        Some(index) => index,
+        None => return current_monitor(window).ok_or(WindowModeError::NoMonitors),
+    };
+
+    let monitors: Vec<_> = window.available_monitors().collect();
+    let count = monitors.len();
+    monitors
+        .into_iter()
+        .nth(index)
+        .ok_or(WindowModeError::NoSuchMonitor { index, count })
+}
+
+fn pick_video_mode(
+    monitor: &MonitorHandle,
+    request: Option<VideoModeRequest>,
+) -> Result<VideoMode, WindowModeError> {
+    let matches = |mode: &VideoMode| match request {
+        Some(request) => {
+            mode.size() == PhysicalSize::new(request.width, request.height)
+                && request
+                    .refresh_rate
+                    .is_none_or(|hz| mode.refresh_rate() == hz)
+        }
+        None => true,
+    };
+    monitor
+        .video_modes()
+        .filter(matches)
+        .max_by_key(|mode| {
+            let size = mode.size();
+            (
+                size.width as u64 * size.height as u64,
+                mode.refresh_rate(),
+                mode.bit_depth(),
+            )
+        })
+        .ok_or_else(|| WindowModeError::NoVideoMode {
+            monitor: monitor_name(monitor),
+            request,
+        })
+}
+
+// The smallest rectangle holding every monitor, or `None` without monitors.
+fn monitor_bounds(
+    monitors: impl Iterator<Item = MonitorHandle>,
+) -> Option<(PhysicalPosition<i32>, PhysicalSize<u32>)> {
+    bounding_box(monitors.map(|monitor| (monitor.position(), monitor.size())))
+}
+
+fn bounding_box(
+    rects: impl Iterator<Item = (PhysicalPosition<i32>, PhysicalSize<u32>)>,
+) -> Option<(PhysicalPosition<i32>, PhysicalSize<u32>)> {
+    let (min, max) = rects
+        .map(|(position, size)| {
+            let max = [
+                position.x + size.width as i32,
+                position.y + size.height as i32,
+            ];
+            ([position.x, position.y], max)
+        })
+        .reduce(|(a_min, a_max), (b_min, b_max)| {
+            (
+                [a_min[0].min(b_min[0]), a_min[1].min(b_min[1])],
+                [a_max[0].max(b_max[0]), a_max[1].max(b_max[1])],
+            )
+        })?;
+    Some((
+        PhysicalPosition::new(min[0], min[1]),
+        PhysicalSize::new((max[0] - min[0]) as u32, (max[1] - min[1]) as u32),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_modes_and_video_modes() {
+        assert_eq!("span".parse(), Ok(WindowMode::Span));
+        assert!("tiled".parse::<WindowMode>().is_err());
+        assert_eq!(
+            "1920x1080@144".parse(),
+            Ok(VideoModeRequest {
+                width: 1920,
+                height: 1080,
+                refresh_rate: Some(144),
+            })
+        );
+        assert_eq!(
+            "800x600".parse::<VideoModeRequest>().unwrap().refresh_rate,
+            None
+        );
+        assert!("800x".parse::<VideoModeRequest>().is_err());
+    }
+
+    #[test]
+    fn bounding_box_covers_every_monitor() {
+        let monitors = [
+            (PhysicalPosition::new(0, 0), PhysicalSize::new(1920, 1080)),
+            (
+                PhysicalPosition::new(-1280, 200),
+                PhysicalSize::new(1280, 1024),
+            ),
+        ];
+        assert_eq!(
+            bounding_box(monitors.into_iter()),
+            Some((
+                PhysicalPosition::new(-1280, 0),
+                PhysicalSize::new(3200, 1224)
+            ))
+        );
+        assert_eq!(bounding_box(std::iter::empty()), None);
+    }
+}
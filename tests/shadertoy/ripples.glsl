@@ -0,0 +1,14 @@
+// Rings spreading from the centre over iChannel0, written as it would be on
+// shadertoy.com.
+void mainImage(out vec4 fragColor, in vec2 fragCoord)
+{
+    vec2 uv = fragCoord / iResolution.xy;
+    vec2 p = (2.0 * fragCoord - iResolution.xy) / iResolution.y;
+    float d = length(p);
+    float wave = sin(12.0 * d - 4.0 * iTime);
+    vec2 offset = p / max(d, 1e-3) * wave * 0.02;
+    vec3 col = texture(iChannel0, uv * 2.0 + offset).rgb;
+    col *= 0.75 + 0.25 * wave;
+    col = mix(col, vec3(0.2, 0.5, 1.0), smoothstep(0.9, 1.0, d));
+    fragColor = vec4(col, 1.0);
+}